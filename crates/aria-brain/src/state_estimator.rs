@@ -2,11 +2,23 @@
 
 use aria_domain::{IStateEstimator, State, Pose, Twist, RobotMode};
 use chrono::Utc;
-use nalgebra::{Vector3, UnitQuaternion};
+use nalgebra::{SMatrix, SVector, UnitQuaternion, Vector3};
+
+/// Error-state dimension: position (3) + orientation tangent (3) + linear velocity (3).
+const N: usize = 9;
+
+/// Default measurement noise used for the velocity block, which has no
+/// covariance of its own on `Twist`.
+const DEFAULT_VELOCITY_VARIANCE: f32 = 0.05;
+/// Default measurement noise used for the pose block when the incoming
+/// measurement doesn't carry a `Pose::covariance`.
+const DEFAULT_POSE_VARIANCE: f32 = 0.1;
 
 pub struct StateEstimator {
     state: State,
     process_noise: f32,
+    /// Covariance of the 9-dim error state `[position, orientation tangent, velocity]`.
+    covariance: SMatrix<f32, N, N>,
 }
 
 impl StateEstimator {
@@ -28,26 +40,183 @@ impl StateEstimator {
                 custom_state: std::collections::HashMap::new(),
             },
             process_noise: 0.01,
+            covariance: SMatrix::<f32, N, N>::identity(),
+        }
+    }
+
+    /// Constant-velocity process covariance `Q` for a step of `dt`: position
+    /// and orientation drift with `dt`, velocity drifts at the base rate.
+    fn process_covariance(&self, dt: f32) -> SMatrix<f32, N, N> {
+        let mut q = SMatrix::<f32, N, N>::zeros();
+        for i in 0..6 {
+            q[(i, i)] = self.process_noise * dt.max(1e-6);
+        }
+        for i in 6..N {
+            q[(i, i)] = self.process_noise;
+        }
+        q
+    }
+
+    /// Measurement noise `R`: the position/orientation block comes from the
+    /// measurement's own `Pose::covariance` when present, otherwise a default
+    /// variance; the velocity block always uses the default (`Twist` carries
+    /// no covariance of its own).
+    fn measurement_covariance(&self, measurement: &State) -> SMatrix<f32, N, N> {
+        let mut r = SMatrix::<f32, N, N>::zeros();
+        match measurement.pose.covariance {
+            Some(cov) => {
+                for row in 0..6 {
+                    for col in 0..6 {
+                        r[(row, col)] = cov[row * 6 + col];
+                    }
+                }
+            }
+            None => {
+                for i in 0..6 {
+                    r[(i, i)] = DEFAULT_POSE_VARIANCE;
+                }
+            }
+        }
+        for i in 6..N {
+            r[(i, i)] = DEFAULT_VELOCITY_VARIANCE;
         }
+        r
+    }
+
+    /// The pose block (position + orientation) of the state covariance, in
+    /// the row-major `[f32; 36]` layout `Pose::covariance` expects.
+    fn pose_covariance(&self) -> [f32; 36] {
+        let mut cov = [0.0f32; 36];
+        for row in 0..6 {
+            for col in 0..6 {
+                cov[row * 6 + col] = self.covariance[(row, col)];
+            }
+        }
+        cov
     }
 }
 
 impl IStateEstimator for StateEstimator {
     fn predict(&mut self, dt: f32) {
-        // Predict next state using motion model
+        // Constant-velocity motion model: position += velocity * dt,
+        // orientation and velocity carried forward unchanged.
         self.state.pose.position += self.state.velocity.linear * dt;
         self.state.timestamp = Utc::now();
+
+        // F: identity except the position rows pick up dt * velocity.
+        let mut f = SMatrix::<f32, N, N>::identity();
+        f[(0, 6)] = dt;
+        f[(1, 7)] = dt;
+        f[(2, 8)] = dt;
+
+        let q = self.process_covariance(dt);
+        self.covariance = f * self.covariance * f.transpose() + q;
     }
-    
+
     fn update(&mut self, measurement: &State) {
-        // Kalman update (simplified)
-        let alpha = 0.7;
-        self.state.pose.position = self.state.pose.position * (1.0 - alpha) + measurement.pose.position * alpha;
-        self.state.velocity = measurement.velocity;
+        // Innovation y = z - H*x. Position and velocity are observed
+        // directly; orientation is differenced on the quaternion tangent
+        // space (the rotation vector taking the current estimate to the
+        // measurement), since quaternions don't subtract linearly.
+        let pos_innov = measurement.pose.position - self.state.pose.position;
+        let rot_innov = (self.state.pose.orientation.inverse() * measurement.pose.orientation)
+            .scaled_axis();
+        let vel_innov = measurement.velocity.linear - self.state.velocity.linear;
+
+        let y: SVector<f32, N> = SVector::from_iterator(
+            pos_innov
+                .iter()
+                .chain(rot_innov.iter())
+                .chain(vel_innov.iter())
+                .copied(),
+        );
+
+        // H = I (the full error state is observed directly), so
+        // S = H*P*Hᵀ + R reduces to P + R and K = P*Hᵀ*S⁻¹ to P*S⁻¹.
+        let r = self.measurement_covariance(measurement);
+        let s = self.covariance + r;
+        let s_inv = s
+            .try_inverse()
+            .unwrap_or_else(|| SMatrix::<f32, N, N>::identity());
+        let k = self.covariance * s_inv;
+
+        let dx = k * y;
+
+        self.state.pose.position += dx.fixed_rows::<3>(0).into_owned();
+        self.state.pose.orientation = self.state.pose.orientation
+            * UnitQuaternion::from_scaled_axis(dx.fixed_rows::<3>(3).into_owned());
+        self.state.velocity.linear += dx.fixed_rows::<3>(6).into_owned();
+
+        self.covariance = (SMatrix::<f32, N, N>::identity() - k) * self.covariance;
+        self.state.pose.covariance = Some(self.pose_covariance());
+
+        self.state.velocity.angular = measurement.velocity.angular;
         self.state.battery_percent = measurement.battery_percent;
+        self.state.mode = measurement.mode;
+        self.state.custom_state = measurement.custom_state.clone();
     }
-    
+
     fn get_state(&self) -> State {
         self.state.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measurement_at(position: Vector3<f32>, velocity: Vector3<f32>) -> State {
+        State {
+            timestamp: Utc::now(),
+            pose: Pose {
+                position,
+                orientation: UnitQuaternion::identity(),
+                covariance: None,
+            },
+            velocity: Twist {
+                linear: velocity,
+                angular: Vector3::zeros(),
+            },
+            battery_percent: 80.0,
+            mode: RobotMode::Idle,
+            custom_state: std::collections::HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_predict_advances_position_by_velocity() {
+        let mut estimator = StateEstimator::new();
+        estimator.update(&measurement_at(Vector3::zeros(), Vector3::new(1.0, 0.0, 0.0)));
+        estimator.predict(0.5);
+
+        let state = estimator.get_state();
+        assert!((state.pose.position.x - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_predict_grows_covariance() {
+        let mut estimator = StateEstimator::new();
+        let before = estimator.covariance[(0, 0)];
+        estimator.predict(1.0);
+        assert!(estimator.covariance[(0, 0)] > before);
+    }
+
+    #[test]
+    fn test_update_shrinks_covariance() {
+        let mut estimator = StateEstimator::new();
+        estimator.predict(1.0);
+        let before = estimator.covariance[(0, 0)];
+        estimator.update(&measurement_at(Vector3::new(1.0, 0.0, 0.0), Vector3::zeros()));
+        assert!(estimator.covariance[(0, 0)] < before);
+    }
+
+    #[test]
+    fn test_update_pulls_estimate_toward_measurement() {
+        let mut estimator = StateEstimator::new();
+        estimator.update(&measurement_at(Vector3::new(2.0, 0.0, 0.0), Vector3::zeros()));
+
+        let state = estimator.get_state();
+        assert!(state.pose.position.x > 0.0);
+        assert!(state.pose.covariance.is_some());
+    }
+}
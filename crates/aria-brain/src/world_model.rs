@@ -1,25 +1,171 @@
 //! World Model: spatial/temporal state representation
 
 use aria_domain::{IWorldModel, Observation, Entity, Belief, BoundingBox, Pose};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use chrono::{DateTime, Utc};
 
 pub struct WorldModel {
+    node_id: String,
     entities: HashMap<String, EntityState>,
 }
 
+#[derive(Clone)]
 struct EntityState {
     entity: Entity,
     belief: Belief,
     history: Vec<Observation>,
 }
 
+/// Wire-serializable form of a `WorldModel`, exchanged between nodes so they
+/// can gossip partial views of the world and `merge` them back in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldModelDelta {
+    pub entities: Vec<EntityRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityRecord {
+    pub entity: Entity,
+    pub belief: Belief,
+    pub history: Vec<Observation>,
+}
+
+/// Causal relationship between two version vectors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VvOrdering {
+    Dominates,
+    Dominated,
+    Equal,
+    Concurrent,
+}
+
+fn compare_versions(a: &HashMap<String, u64>, b: &HashMap<String, u64>) -> VvOrdering {
+    let mut a_ahead = false;
+    let mut b_ahead = false;
+    for node in a.keys().chain(b.keys()).collect::<std::collections::HashSet<_>>() {
+        let va = a.get(node).copied().unwrap_or(0);
+        let vb = b.get(node).copied().unwrap_or(0);
+        match va.cmp(&vb) {
+            Ordering::Greater => a_ahead = true,
+            Ordering::Less => b_ahead = true,
+            Ordering::Equal => {}
+        }
+    }
+    match (a_ahead, b_ahead) {
+        (false, false) => VvOrdering::Equal,
+        (true, false) => VvOrdering::Dominates,
+        (false, true) => VvOrdering::Dominated,
+        (true, true) => VvOrdering::Concurrent,
+    }
+}
+
+fn merge_versions(a: &HashMap<String, u64>, b: &HashMap<String, u64>) -> HashMap<String, u64> {
+    let mut merged = a.clone();
+    for (node, &count) in b {
+        let entry = merged.entry(node.clone()).or_insert(0);
+        *entry = (*entry).max(count);
+    }
+    merged
+}
+
+/// Canonical, order-independent key for a version vector, used to break
+/// ties between two concurrent beliefs whose `uncertainty` and `last_seen`
+/// are exactly equal. Deriving the key from content (rather than which side
+/// happens to be `mine`/`other`) keeps `merge_entity` commutative: swapping
+/// the two sides yields the same comparison result, not its reverse.
+fn version_tiebreak_key(version: &HashMap<String, u64>) -> Vec<(&String, u64)> {
+    let mut key: Vec<(&String, u64)> = version.iter().map(|(k, &v)| (k, v)).collect();
+    key.sort();
+    key
+}
+
 impl WorldModel {
-    pub fn new() -> Self {
+    pub fn new(node_id: impl Into<String>) -> Self {
         Self {
+            node_id: node_id.into(),
             entities: HashMap::new(),
         }
     }
+
+    /// Snapshot this world model as a serializable delta suitable for
+    /// gossiping to other nodes.
+    pub fn to_delta(&self) -> WorldModelDelta {
+        WorldModelDelta {
+            entities: self
+                .entities
+                .values()
+                .map(|state| EntityRecord {
+                    entity: state.entity.clone(),
+                    belief: state.belief.clone(),
+                    history: state.history.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Merge in a delta received from another node. Commutative, associative
+    /// and idempotent: applying the same (or a reordered) set of deltas any
+    /// number of times converges to the same state.
+    pub fn merge_delta(&mut self, delta: &WorldModelDelta) {
+        for record in &delta.entities {
+            match self.entities.get_mut(&record.entity.id) {
+                None => {
+                    self.entities.insert(
+                        record.entity.id.clone(),
+                        EntityState {
+                            entity: record.entity.clone(),
+                            belief: record.belief.clone(),
+                            history: record.history.clone(),
+                        },
+                    );
+                }
+                Some(mine) => merge_entity(mine, record),
+            }
+        }
+    }
+
+    /// Merge another node's (possibly partial) world model into this one.
+    pub fn merge(&mut self, other: &WorldModel) {
+        self.merge_delta(&other.to_delta());
+    }
+}
+
+fn merge_entity(mine: &mut EntityState, other: &EntityRecord) {
+    let take_other = match compare_versions(&mine.belief.version, &other.belief.version) {
+        VvOrdering::Dominates | VvOrdering::Equal => false,
+        VvOrdering::Dominated => true,
+        VvOrdering::Concurrent => {
+            if mine.belief.uncertainty != other.belief.uncertainty {
+                other.belief.uncertainty < mine.belief.uncertainty
+            } else if mine.belief.last_seen != other.belief.last_seen {
+                other.belief.last_seen > mine.belief.last_seen
+            } else {
+                // Exact tie on both fields: fall back to a content-derived
+                // key so the result doesn't depend on merge direction.
+                version_tiebreak_key(&other.belief.version) > version_tiebreak_key(&mine.belief.version)
+            }
+        }
+    };
+
+    if take_other {
+        mine.entity = other.entity.clone();
+        mine.belief.pose = other.belief.pose.clone();
+        mine.belief.uncertainty = other.belief.uncertainty;
+    }
+    mine.belief.last_seen = mine.belief.last_seen.max(other.belief.last_seen);
+    mine.belief.version = merge_versions(&mine.belief.version, &other.belief.version);
+
+    for obs in &other.history {
+        let already_known = mine
+            .history
+            .iter()
+            .any(|o| o.timestamp == obs.timestamp && o.source == obs.source);
+        if !already_known {
+            mine.history.push(obs.clone());
+        }
+    }
+    mine.history.sort_by_key(|o| o.timestamp);
 }
 
 impl IWorldModel for WorldModel {
@@ -33,19 +179,28 @@ impl IWorldModel for WorldModel {
                         pose: entity.pose.clone(),
                         uncertainty: 1.0,
                         last_seen: observation.timestamp,
+                        version: HashMap::new(),
                     },
                     history: Vec::new(),
                 }
             });
-            
+
             state.entity = entity.clone();
             state.belief.pose = entity.pose;
             state.belief.last_seen = observation.timestamp;
             state.belief.uncertainty *= 0.9; // Reduce uncertainty with observation
-            state.history.push(observation.clone());
+            *state.belief.version.entry(self.node_id.clone()).or_insert(0) += 1;
+
+            let already_known = state
+                .history
+                .iter()
+                .any(|o| o.timestamp == observation.timestamp && o.source == observation.source);
+            if !already_known {
+                state.history.push(observation.clone());
+            }
         }
     }
-    
+
     fn query_region(&self, bbox: &BoundingBox) -> Vec<Entity> {
         self.entities
             .values()
@@ -58,7 +213,7 @@ impl IWorldModel for WorldModel {
             .map(|state| state.entity.clone())
             .collect()
     }
-    
+
     fn get_belief(&self, entity_id: &str) -> Option<Belief> {
         self.entities.get(entity_id).map(|state| state.belief.clone())
     }
@@ -67,30 +222,146 @@ impl IWorldModel for WorldModel {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Utc;
     use nalgebra::{Vector3, UnitQuaternion};
-    
-    #[test]
-    fn test_world_model_update() {
-        let mut world = WorldModel::new();
-        
-        let entity = Entity {
-            id: "obj1".into(),
+
+    fn make_entity(id: &str, x: f32) -> Entity {
+        Entity {
+            id: id.into(),
             class_name: "person".into(),
             pose: Pose {
-                position: Vector3::new(1.0, 2.0, 0.0),
+                position: Vector3::new(x, 2.0, 0.0),
                 orientation: UnitQuaternion::identity(),
                 covariance: None,
             },
             properties: HashMap::new(),
-        };
-        
+        }
+    }
+
+    #[test]
+    fn test_world_model_update() {
+        let mut world = WorldModel::new("node-a");
+
+        let entity = make_entity("obj1", 1.0);
         let obs = Observation {
             timestamp: Utc::now(),
             source: "camera".into(),
             entities: vec![entity.clone()],
         };
-        
+
         world.update(obs);
         assert!(world.get_belief("obj1").is_some());
     }
+
+    #[test]
+    fn test_merge_takes_causally_newer_update() {
+        let mut a = WorldModel::new("node-a");
+        a.update(Observation {
+            timestamp: Utc::now(),
+            source: "camera".into(),
+            entities: vec![make_entity("obj1", 1.0)],
+        });
+
+        let mut b = WorldModel::new("node-b");
+        b.merge(&a);
+        b.update(Observation {
+            timestamp: Utc::now(),
+            source: "lidar".into(),
+            entities: vec![make_entity("obj1", 5.0)],
+        });
+
+        // b's belief causally descends from a's (it merged a's state first),
+        // so merging b into a should adopt b's newer pose.
+        a.merge(&b);
+        assert_eq!(a.get_belief("obj1").unwrap().pose.position.x, 5.0);
+    }
+
+    #[test]
+    fn test_merge_is_idempotent_and_commutative() {
+        let mut a = WorldModel::new("node-a");
+        a.update(Observation {
+            timestamp: Utc::now(),
+            source: "camera".into(),
+            entities: vec![make_entity("obj1", 1.0)],
+        });
+
+        let mut b = WorldModel::new("node-b");
+        b.update(Observation {
+            timestamp: Utc::now(),
+            source: "camera".into(),
+            entities: vec![make_entity("obj1", 2.0)],
+        });
+
+        let mut ab = WorldModel::new("node-a");
+        ab.merge(&a);
+        ab.merge(&b);
+        let ab_pose = ab.get_belief("obj1").unwrap().pose.position.x;
+
+        let mut ba = WorldModel::new("node-b");
+        ba.merge(&b);
+        ba.merge(&a);
+        let ba_pose = ba.get_belief("obj1").unwrap().pose.position.x;
+
+        assert_eq!(ab_pose, ba_pose);
+
+        // Re-merging the same delta again must not change anything.
+        ab.merge(&a);
+        ab.merge(&b);
+        assert_eq!(ab.get_belief("obj1").unwrap().pose.position.x, ab_pose);
+    }
+
+    #[test]
+    fn test_merge_concurrent_exact_tie_is_commutative() {
+        let ts = Utc::now();
+
+        let mut a = WorldModel::new("node-a");
+        a.update(Observation {
+            timestamp: ts,
+            source: "camera".into(),
+            entities: vec![make_entity("obj1", 1.0)],
+        });
+
+        let mut b = WorldModel::new("node-b");
+        b.update(Observation {
+            timestamp: ts,
+            source: "camera".into(),
+            entities: vec![make_entity("obj1", 2.0)],
+        });
+
+        // Both updates land with the same `uncertainty` and `last_seen`, so
+        // `compare_versions` reports `Concurrent` with an exact tie on the
+        // usual tie-breakers. The outcome must not depend on merge direction.
+        let mut ab = WorldModel::new("node-a");
+        ab.merge(&a);
+        ab.merge(&b);
+
+        let mut ba = WorldModel::new("node-b");
+        ba.merge(&b);
+        ba.merge(&a);
+
+        assert_eq!(
+            ab.get_belief("obj1").unwrap().pose.position.x,
+            ba.get_belief("obj1").unwrap().pose.position.x
+        );
+    }
+
+    #[test]
+    fn test_merge_unions_history_without_duplicates() {
+        let ts = Utc::now();
+        let mut a = WorldModel::new("node-a");
+        a.update(Observation {
+            timestamp: ts,
+            source: "camera".into(),
+            entities: vec![make_entity("obj1", 1.0)],
+        });
+
+        let mut b = WorldModel::new("node-b");
+        b.merge(&a); // b now has the same single history entry as a
+
+        a.merge(&b);
+        // Same (timestamp, source) observation merged back in should not duplicate.
+        let delta = a.to_delta();
+        let record = delta.entities.iter().find(|r| r.entity.id == "obj1").unwrap();
+        assert_eq!(record.history.len(), 1);
+    }
 }
@@ -26,14 +26,20 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("ARIA Brain Demo starting...");
     
     // Initialize components
-    let mut world_model = WorldModel::new();
+    let mut world_model = WorldModel::new("aria-demo-brain");
     let mut state_estimator = StateEstimator::new();
     let goal_manager = GoalManager::new();
     let mut task_planner = TaskPlanner::new();
     let mut scheduler = Scheduler::new();
     let policy_manager = PolicyManager::new();
     let rule_checker = RuleChecker::new();
-    let safety_supervisor = SafetySupervisor::new();
+    let mut safety_supervisor = SafetySupervisor::new();
+    let selftest_report = safety_supervisor.run_startup_selftest();
+    if selftest_report.passed() {
+        tracing::info!("Crypto self-test passed; emergency stop not latched at startup");
+    } else {
+        tracing::error!("Crypto self-test failed; emergency stop latched, all actions will be zeroed");
+    }
     let action_synthesizer = ActionSynthesizer::new();
     let action_justifier = ActionJustifier::new();
     
@@ -15,7 +15,15 @@ struct Args {
     /// Decrypt messages
     #[arg(short, long)]
     decrypt: bool,
-    
+
+    /// Shared secret to populate the --decrypt trust store with: every node
+    /// configured with the same secret derives and trusts the same Ed25519
+    /// identity (see `KeyManager::from_shared_secret`). Without this, the
+    /// trust store starts empty and encrypted envelopes are reported but
+    /// left sealed, since there's no peer key to verify them against.
+    #[arg(long)]
+    shared_secret: Option<String>,
+
     /// Output format (json, text)
     #[arg(short, long, default_value = "text")]
     format: String,
@@ -30,16 +38,88 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Listening on topic '{}'", args.topic);
     
     let mut transport = QuicTransport::new();
-    let codec = ProtobufCodec::new();
-    
+
+    // Demo identity for --decrypt: a real deployment looks this up from the
+    // trust store instead of minting it per run.
+    let recipient = args.decrypt.then(|| RecipientKeyPair::generate("aria-recv".into()));
+    let key_manager = match (&recipient, &args.shared_secret) {
+        (Some(_), Some(secret)) => Some(KeyManager::from_shared_secret(secret.as_bytes())?),
+        (Some(_), None) => Some(KeyManager::new()),
+        (None, _) => None,
+    };
+    if let Some(recipient) = &recipient {
+        tracing::info!("Decryption enabled for recipient '{}'", recipient.key_id());
+    }
+
+    // Shared-secret mode trusts by possession of the secret, not by node
+    // id - every node deriving the same identity is trusted under the
+    // fixed "shared-secret" key (see `KeyManager::from_shared_secret`), so
+    // the lookup must use that id instead of whatever the sender happened
+    // to tag itself as in `source_node`.
+    let shared_secret_mode = args.shared_secret.is_some();
+
+    let format = args.format.clone();
+    transport
+        .on_receive(Box::new(move |mut envelope| {
+            if let (Some(recipient), Some(key_manager)) = (&recipient, &key_manager) {
+                if envelope.metadata.crypto_info.is_some() {
+                    let peer_id = if shared_secret_mode {
+                        "shared-secret"
+                    } else {
+                        envelope.metadata.source_node.as_str()
+                    };
+                    match key_manager.trusted_peer(peer_id) {
+                        Some(sender) => match open(&mut envelope, recipient, sender) {
+                            Ok(()) => tracing::info!("Decrypted envelope {}", envelope.id),
+                            Err(e) => tracing::warn!(
+                                "Envelope {} failed to decrypt: {e}",
+                                envelope.id
+                            ),
+                        },
+                        None => tracing::warn!(
+                            "Envelope {} is encrypted but '{}' is not a trusted peer; leaving it sealed",
+                            envelope.id,
+                            peer_id
+                        ),
+                    }
+                }
+            }
+
+            // Pick the decoder the sender tagged the payload with instead of
+            // assuming Protobuf.
+            let codec = codec_for(envelope.metadata.codec);
+            let decoded = codec.decode(&envelope.payload, envelope.schema_id);
+
+            match format.as_str() {
+                "json" => println!(
+                    "{}",
+                    serde_json::json!({
+                        "id": envelope.id.to_string(),
+                        "topic": envelope.topic,
+                        "priority": format!("{:?}", envelope.priority),
+                        "schema_id": envelope.schema_id,
+                        "payload_len": envelope.payload.len(),
+                        "decoded": decoded.is_ok(),
+                    })
+                ),
+                _ => println!(
+                    "[{:?}] {} ({} bytes, schema {}, decoded={})",
+                    envelope.priority,
+                    envelope.topic,
+                    envelope.payload.len(),
+                    envelope.schema_id,
+                    decoded.is_ok()
+                ),
+            }
+        }))
+        .await;
+
     tracing::info!("aria-recv ready");
-    
-    // In production: set up receive handler
-    // transport.on_receive(Box::new(|envelope| {
-    //     println!("Received: {:?}", envelope);
-    // })).await;
-    
-    tokio::signal::ctrl_c().await?;
-    
+
+    tokio::select! {
+        _ = transport.run_receive_loop() => {}
+        result = tokio::signal::ctrl_c() => result?,
+    }
+
     Ok(())
 }
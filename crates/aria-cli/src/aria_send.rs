@@ -29,6 +29,10 @@ struct Args {
     /// FEC redundancy (k,m)
     #[arg(long, value_names = &["k", "m"])]
     fec: Option<Vec<usize>>,
+
+    /// Wire codec for the payload (protobuf, cbor, msgpack)
+    #[arg(long, default_value = "protobuf")]
+    codec: String,
 }
 
 #[tokio::main]
@@ -43,7 +47,13 @@ async fn main() -> anyhow::Result<()> {
         2 => Priority::P2,
         _ => Priority::P3,
     };
-    
+
+    let codec_kind = match args.codec.to_lowercase().as_str() {
+        "cbor" => CodecKind::Cbor,
+        "msgpack" => CodecKind::Msgpack,
+        _ => CodecKind::Protobuf,
+    };
+
     tracing::info!(
         "Sending {} messages to topic '{}' with priority {:?}",
         args.count,
@@ -52,11 +62,16 @@ async fn main() -> anyhow::Result<()> {
     );
     
     let mut transport = QuicTransport::new();
-    let codec = ProtobufCodec::new();
+    let codec = codec_for(codec_kind);
     let compressor = Lz4Compressor::new(1);
-    
+
+    // Demo identities for --encrypt: a real deployment looks these up from
+    // the trust store instead of minting them per run.
+    let recipient = args.encrypt.then(|| RecipientKeyPair::generate("aria-recv".into()));
+    let identity = args.encrypt.then(SigningIdentity::generate);
+
     for i in 0..args.count {
-        let envelope = Envelope {
+        let mut envelope = Envelope {
             id: Uuid::new_v4(),
             timestamp: Utc::now(),
             schema_id: 1,
@@ -66,15 +81,22 @@ async fn main() -> anyhow::Result<()> {
             metadata: EnvelopeMetadata {
                 source_node: "aria-send".into(),
                 sequence_number: i as u64,
+                group_id: None,
                 fragment_info: None,
                 fec_info: None,
                 crypto_info: None,
                 qos_class: "default".into(),
+                codec: codec_kind,
             },
         };
-        
+
+        if let (Some(recipient), Some(identity)) = (&recipient, &identity) {
+            seal(&mut envelope, recipient, identity)?;
+            tracing::info!("Sealed envelope {} for recipient '{}'", envelope.id, recipient.key_id());
+        }
+
         tracing::info!("Sent envelope {}: {}", i, envelope.id);
-        
+
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
     }
     
@@ -6,6 +6,7 @@ use chrono::{DateTime, Utc};
 use nalgebra::{Vector3, UnitQuaternion};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ops::{Add, Div, Mul, Sub};
 use uuid::Uuid;
 
 /// Envelope wraps all messages in the telemetry system
@@ -32,10 +33,26 @@ pub enum Priority {
 pub struct EnvelopeMetadata {
     pub source_node: String,
     pub sequence_number: u64,
+    /// Shared by every fragment/shard split off the same original envelope, so the
+    /// receiver can group them regardless of how many hops assign their own `id`.
+    pub group_id: Option<Uuid>,
     pub fragment_info: Option<FragmentInfo>,
     pub fec_info: Option<FecInfo>,
     pub crypto_info: Option<CryptoInfo>,
     pub qos_class: String,
+    /// Which `ICodec` implementation encoded `Envelope::payload`, so the
+    /// receiver can pick the matching decoder instead of assuming Protobuf.
+    pub codec: CodecKind,
+}
+
+/// Wire-format discriminant for `Envelope::payload`. Carried in
+/// `EnvelopeMetadata` so the receiver doesn't have to guess, or be
+/// statically configured with, a single codec for every topic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CodecKind {
+    Protobuf,
+    Cbor,
+    Msgpack,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,6 +67,10 @@ pub struct FecInfo {
     pub k: u32, // Original data blocks
     pub m: u32, // Redundancy blocks
     pub block_id: u32,
+    /// Index of this fragment's shard within the `k + m` set.
+    pub shard_index: u32,
+    /// Length of the payload before RS padding, so the decoder can trim it back off.
+    pub original_len: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,6 +78,10 @@ pub struct CryptoInfo {
     pub signature: Vec<u8>,
     pub key_id: String,
     pub nonce: Vec<u8>,
+    /// Ephemeral X25519 public key used for the ECDH content-key wrap.
+    pub ephemeral_public_key: Vec<u8>,
+    /// Content key, AES-256-GCM-wrapped under the ECDH-derived KEK.
+    pub wrapped_key: Vec<u8>,
 }
 
 /// Raw sensor sample
@@ -257,3 +282,308 @@ pub enum AudioEventType {
     Silence,
     Unknown,
 }
+
+/// Raw PCM sample encodings exposed by typical audio HALs, as delivered by a
+/// capture device over an interleaved `&[u8]` buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SampleFormat {
+    /// Unsigned 8-bit, centered at 128.
+    U8,
+    /// Signed 16-bit little-endian.
+    S16Le,
+    /// Signed 24-bit little-endian, stored left-justified in a 32-bit word.
+    S24In32Le,
+    /// 32-bit float little-endian, already in [-1, 1].
+    F32Le,
+}
+
+impl SampleFormat {
+    /// Size in bytes of one sample of this format.
+    pub fn sample_size(&self) -> usize {
+        match self {
+            SampleFormat::U8 => 1,
+            SampleFormat::S16Le => 2,
+            SampleFormat::S24In32Le => 4,
+            SampleFormat::F32Le => 4,
+        }
+    }
+}
+
+/// Decode an interleaved raw PCM buffer into per-channel normalized `f32`
+/// samples in `[-1, 1]`. Trailing bytes that don't fill a whole
+/// `channels`-wide frame are dropped.
+pub fn decode_pcm(bytes: &[u8], fmt: SampleFormat, channels: u16) -> Vec<Vec<f32>> {
+    let channels = channels.max(1) as usize;
+    let sample_size = fmt.sample_size();
+    let frame_size = sample_size * channels;
+    if frame_size == 0 {
+        return vec![Vec::new(); channels];
+    }
+
+    let num_frames = bytes.len() / frame_size;
+    let mut out: Vec<Vec<f32>> = (0..channels).map(|_| Vec::with_capacity(num_frames)).collect();
+
+    for frame in bytes.chunks_exact(frame_size).take(num_frames) {
+        for (ch, sample_bytes) in frame.chunks_exact(sample_size).enumerate() {
+            out[ch].push(decode_sample(sample_bytes, fmt));
+        }
+    }
+
+    out
+}
+
+fn decode_sample(bytes: &[u8], fmt: SampleFormat) -> f32 {
+    match fmt {
+        SampleFormat::U8 => (bytes[0] as f32 - 128.0) / 128.0,
+        SampleFormat::S16Le => {
+            let s = i16::from_le_bytes([bytes[0], bytes[1]]);
+            s as f32 / 32768.0
+        }
+        SampleFormat::S24In32Le => {
+            // Left-justified in a 32-bit word: sign-extend by shifting the
+            // top 24 bits up to the word boundary, then back down.
+            let word = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            (word >> 8) as f32 / 8_388_608.0 // 2^23
+        }
+        SampleFormat::F32Le => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    }
+}
+
+/// Backing integer for `ClockDuration`: femtoseconds fit in a `u128` on
+/// native targets, but 128-bit arithmetic is pathologically slow on wasm32,
+/// so that target trades range (overflows past ~5.1 hours, since
+/// `u64::MAX / FEMTOS_PER_SEC` is ~18,447 seconds) for speed.
+#[cfg(not(target_arch = "wasm32"))]
+pub type ClockDurationRepr = u128;
+#[cfg(target_arch = "wasm32")]
+pub type ClockDurationRepr = u64;
+
+const FEMTOS_PER_NANO: ClockDurationRepr = 1_000_000;
+const FEMTOS_PER_MICRO: ClockDurationRepr = 1_000_000_000;
+const FEMTOS_PER_MILLI: ClockDurationRepr = 1_000_000_000_000;
+const FEMTOS_PER_SEC: ClockDurationRepr = 1_000_000_000_000_000;
+
+/// Elapsed time stored as an exact count of femtoseconds, so network jitter
+/// well below a millisecond and slow ppm-level clock drift survive
+/// round-trips through CCEM instead of being quantized away by
+/// `Duration::from_millis`/`as_millis`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ClockDuration(ClockDurationRepr);
+
+impl ClockDuration {
+    pub const ZERO: ClockDuration = ClockDuration(0);
+
+    pub fn from_nanos(nanos: u64) -> Self {
+        ClockDuration((nanos as ClockDurationRepr).saturating_mul(FEMTOS_PER_NANO))
+    }
+
+    pub fn from_micros(micros: u64) -> Self {
+        ClockDuration((micros as ClockDurationRepr).saturating_mul(FEMTOS_PER_MICRO))
+    }
+
+    pub fn from_millis(millis: u64) -> Self {
+        ClockDuration((millis as ClockDurationRepr).saturating_mul(FEMTOS_PER_MILLI))
+    }
+
+    pub fn from_secs(secs: u64) -> Self {
+        ClockDuration((secs as ClockDurationRepr).saturating_mul(FEMTOS_PER_SEC))
+    }
+
+    /// Build from a (possibly fractional) second count, e.g. a PI-filter
+    /// offset estimate. Negative input saturates to `ZERO` — this type only
+    /// represents elapsed time, never a signed offset.
+    pub fn from_secs_f64(secs: f64) -> Self {
+        if secs <= 0.0 {
+            return ClockDuration::ZERO;
+        }
+        ClockDuration((secs * FEMTOS_PER_SEC as f64) as ClockDurationRepr)
+    }
+
+    pub fn as_femtos(&self) -> ClockDurationRepr {
+        self.0
+    }
+
+    pub fn as_secs_f64(&self) -> f64 {
+        self.0 as f64 / FEMTOS_PER_SEC as f64
+    }
+}
+
+impl Default for ClockDuration {
+    fn default() -> Self {
+        ClockDuration::ZERO
+    }
+}
+
+impl Add for ClockDuration {
+    type Output = ClockDuration;
+    fn add(self, rhs: Self) -> Self {
+        ClockDuration(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl Sub for ClockDuration {
+    type Output = ClockDuration;
+    fn sub(self, rhs: Self) -> Self {
+        ClockDuration(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl Mul<u64> for ClockDuration {
+    type Output = ClockDuration;
+    fn mul(self, rhs: u64) -> Self {
+        ClockDuration(self.0.saturating_mul(rhs as ClockDurationRepr))
+    }
+}
+
+impl Div<u64> for ClockDuration {
+    type Output = ClockDuration;
+    fn div(self, rhs: u64) -> Self {
+        if rhs == 0 {
+            ClockDuration(ClockDurationRepr::MAX)
+        } else {
+            ClockDuration(self.0 / rhs as ClockDurationRepr)
+        }
+    }
+}
+
+impl From<std::time::Duration> for ClockDuration {
+    fn from(d: std::time::Duration) -> Self {
+        let femtos = (d.as_secs() as ClockDurationRepr)
+            .saturating_mul(FEMTOS_PER_SEC)
+            .saturating_add((d.subsec_nanos() as ClockDurationRepr).saturating_mul(FEMTOS_PER_NANO));
+        ClockDuration(femtos)
+    }
+}
+
+impl From<ClockDuration> for std::time::Duration {
+    fn from(c: ClockDuration) -> Self {
+        let total_nanos = c.0 / FEMTOS_PER_NANO;
+        let secs = (total_nanos / 1_000_000_000).min(u64::MAX as ClockDurationRepr) as u64;
+        let subsec_nanos = (total_nanos % 1_000_000_000) as u32;
+        std::time::Duration::new(secs, subsec_nanos)
+    }
+}
+
+impl From<chrono::Duration> for ClockDuration {
+    fn from(d: chrono::Duration) -> Self {
+        let nanos = d.num_nanoseconds().unwrap_or(0).max(0) as ClockDurationRepr;
+        ClockDuration(nanos.saturating_mul(FEMTOS_PER_NANO))
+    }
+}
+
+impl From<ClockDuration> for chrono::Duration {
+    fn from(c: ClockDuration) -> Self {
+        let total_nanos = (c.0 / FEMTOS_PER_NANO).min(i64::MAX as ClockDurationRepr) as i64;
+        chrono::Duration::nanoseconds(total_nanos)
+    }
+}
+
+#[cfg(test)]
+mod clock_duration_tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrips_std_duration() {
+        let original = std::time::Duration::new(3, 250_000_000); // 3.25s
+        let clock: ClockDuration = original.into();
+        let back: std::time::Duration = clock.into();
+        assert_eq!(back, original);
+    }
+
+    #[test]
+    fn test_roundtrips_chrono_duration() {
+        let original = chrono::Duration::microseconds(1_500);
+        let clock: ClockDuration = original.into();
+        let back: chrono::Duration = clock.into();
+        assert_eq!(back, original);
+    }
+
+    #[test]
+    fn test_sub_millisecond_precision_survives() {
+        let tiny = ClockDuration::from_nanos(250); // 0.25us, rounds to 0ms
+        assert!(tiny.as_femtos() > 0);
+        assert!(tiny.as_secs_f64() > 0.0);
+    }
+
+    #[test]
+    fn test_add_and_sub_are_saturating() {
+        let max = ClockDuration(ClockDurationRepr::MAX);
+        assert_eq!((max + ClockDuration::from_secs(1)).as_femtos(), ClockDurationRepr::MAX);
+        assert_eq!((ClockDuration::ZERO - ClockDuration::from_secs(1)), ClockDuration::ZERO);
+    }
+
+    #[test]
+    fn test_mul_is_saturating() {
+        let big = ClockDuration(ClockDurationRepr::MAX / 2);
+        assert_eq!((big * 3).as_femtos(), ClockDurationRepr::MAX);
+    }
+
+    #[test]
+    fn test_from_chrono_duration_is_saturating() {
+        let d = chrono::Duration::nanoseconds(i64::MAX);
+        let expected = (i64::MAX as ClockDurationRepr).saturating_mul(FEMTOS_PER_NANO);
+        assert_eq!(ClockDuration::from(d).as_femtos(), expected);
+    }
+
+    #[test]
+    fn test_div_by_zero_saturates_instead_of_panicking() {
+        let d = ClockDuration::from_secs(5);
+        assert_eq!((d / 0).as_femtos(), ClockDurationRepr::MAX);
+    }
+
+    #[test]
+    fn test_div_and_mul_are_inverse_for_exact_multiples() {
+        let d = ClockDuration::from_millis(100);
+        assert_eq!((d * 3) / 3, d);
+    }
+}
+
+#[cfg(test)]
+mod pcm_tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_u8_centers_and_scales() {
+        let bytes = [0u8, 128, 255];
+        let decoded = decode_pcm(&bytes, SampleFormat::U8, 1);
+        assert_eq!(decoded.len(), 1);
+        assert!((decoded[0][0] - (-1.0)).abs() < 1e-3);
+        assert!((decoded[0][1] - 0.0).abs() < 1e-3);
+        assert!((decoded[0][2] - 0.992).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_decode_s16le_roundtrips_extremes() {
+        let bytes = [0x00, 0x80, 0xFF, 0x7F]; // i16::MIN, i16::MAX
+        let decoded = decode_pcm(&bytes, SampleFormat::S16Le, 1);
+        assert!((decoded[0][0] - (-1.0)).abs() < 1e-3);
+        assert!((decoded[0][1] - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_decode_deinterleaves_channels() {
+        // Two interleaved S16LE channels: ch0 = 0, ch1 = i16::MAX.
+        let frame = [0x00, 0x00, 0xFF, 0x7F];
+        let bytes: Vec<u8> = frame.iter().cycle().take(frame.len() * 3).copied().collect();
+        let decoded = decode_pcm(&bytes, SampleFormat::S16Le, 2);
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].len(), 3);
+        assert!(decoded[0].iter().all(|&s| s.abs() < 1e-3));
+        assert!(decoded[1].iter().all(|&s| (s - 1.0).abs() < 1e-3));
+    }
+
+    #[test]
+    fn test_decode_s24in32le_sign_extends() {
+        // Most negative left-justified 24-bit value: top byte 0x80, rest 0.
+        let bytes = [0x00, 0x00, 0x00, 0x80];
+        let decoded = decode_pcm(&bytes, SampleFormat::S24In32Le, 1);
+        assert!((decoded[0][0] - (-1.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_decode_drops_trailing_partial_frame() {
+        let bytes = [0x00, 0x00, 0xFF]; // one full S16 sample + one stray byte
+        let decoded = decode_pcm(&bytes, SampleFormat::S16Le, 1);
+        assert_eq!(decoded[0].len(), 1);
+    }
+}
@@ -5,7 +5,11 @@
 use crate::entities::*;
 use crate::error::AriaResult;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
+use std::collections::HashMap;
 
 // ============================================================================
 // Sensor & Actuator Ports
@@ -116,6 +120,16 @@ pub struct QoSPolicy {
     pub max_queue_depth: usize,
 }
 
+pub trait IObfuscator: Send + Sync {
+    /// Wrap an already-sign-then-encrypted frame for the wire, decoupling
+    /// its on-wire size from its payload size
+    fn obfuscate(&mut self, frame: &[u8]) -> Vec<u8>;
+
+    /// Unwrap bytes read off the wire back into zero or more frames,
+    /// re-segmenting across reads that split or coalesce wire writes
+    fn deobfuscate(&mut self, wire: &[u8]) -> AriaResult<Vec<Vec<u8>>>;
+}
+
 #[async_trait]
 pub trait ITransport: Send + Sync {
     /// Send an envelope
@@ -175,9 +189,31 @@ pub trait ISlamEstimator: Send + Sync {
 pub trait IAudioProcessor: Send + Sync {
     /// Detect voice activity
     fn detect_vad(&mut self, audio: &[f32], sample_rate: u32) -> AriaResult<bool>;
-    
+
     /// Detect sound events
     fn detect_sed(&mut self, audio: &[f32], sample_rate: u32) -> AriaResult<Vec<AudioEvent>>;
+
+    /// Decode a device-native raw PCM buffer and run VAD on it, so sensor
+    /// adapters can push HAL buffers directly instead of every caller
+    /// writing its own `SampleFormat` conversion. Multi-channel buffers are
+    /// mixed down to mono (averaged) before `detect_vad` sees them.
+    fn detect_vad_raw(
+        &mut self,
+        bytes: &[u8],
+        fmt: SampleFormat,
+        channels: u16,
+        sample_rate: u32,
+    ) -> AriaResult<bool> {
+        let decoded = decode_pcm(bytes, fmt, channels);
+        let num_frames = decoded.first().map(|ch| ch.len()).unwrap_or(0);
+        let mut mono = vec![0.0f32; num_frames];
+        for channel in &decoded {
+            for (i, &sample) in channel.iter().enumerate() {
+                mono[i] += sample / decoded.len() as f32;
+            }
+        }
+        self.detect_vad(&mono, sample_rate)
+    }
 }
 
 // ============================================================================
@@ -195,14 +231,14 @@ pub trait IWorldModel: Send + Sync {
     fn get_belief(&self, entity_id: &str) -> Option<Belief>;
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Observation {
     pub timestamp: DateTime<Utc>,
     pub source: String,
     pub entities: Vec<Entity>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Entity {
     pub id: String,
     pub class_name: String,
@@ -210,12 +246,15 @@ pub struct Entity {
     pub properties: std::collections::HashMap<String, f32>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Belief {
     pub entity_id: String,
     pub pose: Pose,
     pub uncertainty: f32,
     pub last_seen: DateTime<Utc>,
+    /// Per-node observation counter (node id -> count), used to resolve
+    /// concurrent updates from multiple ARIA nodes during `WorldModel::merge`.
+    pub version: HashMap<String, u64>,
 }
 
 pub trait IStateEstimator: Send + Sync {
@@ -286,7 +325,7 @@ pub trait IRuleChecker: Send + Sync {
 
 pub trait ISafetySupervisor: Send + Sync {
     /// Override/veto unsafe actions
-    fn supervise(&self, action: Command, state: &State) -> AriaResult<Command>;
+    fn supervise(&mut self, action: Command, state: &State) -> AriaResult<Command>;
     
     /// Emergency stop
     fn emergency_stop(&mut self) -> Command;
@@ -323,6 +362,10 @@ pub struct SystemMetrics {
     pub cpu_usage: f32,
     pub memory_mb: f32,
     pub bandwidth_mbps: f32,
+    /// Smoothed inter-arrival jitter estimate from the RX de-jitter buffer, in ms.
+    pub jitter_ms: f32,
+    /// Current playout delay `RxDeJitter` has settled on, in ms.
+    pub playout_delay_ms: f32,
 }
 
 #[derive(Debug, Clone)]
@@ -5,6 +5,8 @@ use aria_domain::{IHomeostasis, SystemMetrics, HomeostasisAdvice};
 pub struct HomeostasisController {
     target_latency: f32,
     target_loss_rate: f32,
+    target_jitter_ms: f32,
+    target_playout_delay_ms: f32,
 }
 
 impl HomeostasisController {
@@ -12,6 +14,8 @@ impl HomeostasisController {
         Self {
             target_latency: 50.0,
             target_loss_rate: 0.05,
+            target_jitter_ms: 10.0,
+            target_playout_delay_ms: 100.0,
         }
     }
 }
@@ -23,16 +27,31 @@ impl IHomeostasis for HomeostasisController {
             adjust_fec: None,
             adjust_codec: None,
         };
-        
+
         if metrics.latency_ms > self.target_latency * 1.5 {
             advice.adjust_rate = Some(0.8); // Reduce rate
             advice.adjust_codec = Some("LZ4".into()); // Faster codec
         }
-        
+
         if metrics.packet_loss_rate > self.target_loss_rate {
             advice.adjust_fec = Some((4, 2)); // More redundancy
         }
-        
+
+        if metrics.jitter_ms > self.target_jitter_ms {
+            // Jitter this far above target means the network path itself is
+            // unstable; shedding rate gives the de-jitter buffer more room
+            // to widen its playout window without falling further behind.
+            advice.adjust_rate = Some(advice.adjust_rate.unwrap_or(1.0).min(0.8));
+        }
+
+        if metrics.playout_delay_ms > self.target_playout_delay_ms {
+            // RxDeJitter has already widened its playout window past target,
+            // which only happens once jitter has been elevated for a while -
+            // same remedy as the jitter_ms case, just a lagging confirmation
+            // of it rather than a separate cause.
+            advice.adjust_rate = Some(advice.adjust_rate.unwrap_or(1.0).min(0.8));
+        }
+
         advice
     }
 }
@@ -1,7 +1,9 @@
 //! Audio processing: VAD, SED, ASR, DSP
 
-use aria_domain::{AriaResult, AudioEvent, AudioEventType, IAudioProcessor, AriaError};
+use aria_domain::{AriaResult, AudioEvent, AudioEventType, IAudioProcessor, AriaError, SampleFormat};
 use chrono::Utc;
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
 
 pub struct AudioProcessor {
     vad_threshold: f32,
@@ -44,37 +46,287 @@ impl IAudioProcessor for AudioProcessor {
     }
 }
 
+/// Speed of sound in air, m/s, used to turn an inter-channel sample delay
+/// into a direction-of-arrival estimate.
+const SOUND_SPEED_MPS: f32 = 343.0;
+const PHAT_EPS: f32 = 1e-10;
+
+const DEFAULT_MIC_SPACING_M: f32 = 0.05;
+const DEFAULT_SAMPLE_RATE: u32 = 16000;
+/// Default over-subtraction factor `alpha` for spectral-subtraction denoising.
+const DEFAULT_OVERSUBTRACTION_ALPHA: f32 = 2.0;
+/// Default spectral floor, in dB, below which subtraction doesn't dig further.
+const DEFAULT_SPECTRAL_FLOOR_DB: f32 = -20.0;
+/// Analysis/synthesis frame size for denoising, with 50% (`FRAME_SIZE / 2`) overlap.
+const DENOISE_FRAME_SIZE: usize = 512;
+/// EMA smoothing factor for the running noise magnitude estimate.
+const NOISE_EMA_ALPHA: f32 = 0.1;
+
+/// Convert a gain expressed in dB to a linear magnitude multiplier.
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Periodic Hann window of length `len`, used for both analysis and
+/// synthesis so overlap-add reconstruction stays a constant-overlap sum.
+fn hann_window(len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / len as f32).cos())
+        .collect()
+}
+
+/// Result of delay-and-sum beamforming: the aligned/summed signal plus the
+/// direction-of-arrival estimate derived from the delay to the reference
+/// channel, when there was more than one channel to measure it from.
+#[derive(Debug, Clone)]
+pub struct BeamformOutput {
+    pub samples: Vec<f32>,
+    pub doa_rad: Option<f32>,
+}
+
 pub struct AudioDsp {
     num_channels: usize,
+    mic_spacing_m: f32,
+    sample_rate: u32,
+    oversubtraction_alpha: f32,
+    spectral_floor_db: f32,
+    /// Running EMA of the noise magnitude spectrum, updated only on frames
+    /// the VAD gate classifies as non-voice. `None` until the first frame.
+    noise_mag: Option<Vec<f32>>,
 }
 
 impl AudioDsp {
     pub fn new(num_channels: usize) -> Self {
-        Self { num_channels }
+        Self::with_geometry(num_channels, DEFAULT_MIC_SPACING_M, DEFAULT_SAMPLE_RATE)
     }
-    
-    pub fn beamform(&self, multi_channel: &[Vec<f32>]) -> Vec<f32> {
-        // Simple delay-and-sum beamforming
+
+    /// `mic_spacing_m` is the spacing between adjacent mics of a linear
+    /// array, used to convert delays into a direction-of-arrival estimate.
+    pub fn with_geometry(num_channels: usize, mic_spacing_m: f32, sample_rate: u32) -> Self {
+        Self::with_denoise_params(
+            num_channels,
+            mic_spacing_m,
+            sample_rate,
+            DEFAULT_OVERSUBTRACTION_ALPHA,
+            DEFAULT_SPECTRAL_FLOOR_DB,
+        )
+    }
+
+    /// `oversubtraction_alpha` and `spectral_floor_db` tune the
+    /// spectral-subtraction denoiser (see `denoise`).
+    pub fn with_denoise_params(
+        num_channels: usize,
+        mic_spacing_m: f32,
+        sample_rate: u32,
+        oversubtraction_alpha: f32,
+        spectral_floor_db: f32,
+    ) -> Self {
+        Self {
+            num_channels,
+            mic_spacing_m,
+            sample_rate,
+            oversubtraction_alpha,
+            spectral_floor_db,
+            noise_mag: None,
+        }
+    }
+
+    /// Delay-and-sum beamforming: each channel is time-aligned to channel 0
+    /// via GCC-PHAT before summing, so off-axis sources add coherently
+    /// instead of being smeared by their inter-mic delay.
+    pub fn beamform(&self, multi_channel: &[Vec<f32>]) -> BeamformOutput {
         if multi_channel.is_empty() {
-            return vec![];
+            return BeamformOutput {
+                samples: vec![],
+                doa_rad: None,
+            };
         }
-        
+        if multi_channel.len() == 1 {
+            return BeamformOutput {
+                samples: multi_channel[0].clone(),
+                doa_rad: None,
+            };
+        }
+
         let len = multi_channel[0].len();
-        let mut output = vec![0.0f32; len];
-        
-        for channel in multi_channel {
-            for (i, &sample) in channel.iter().enumerate() {
-                output[i] += sample / multi_channel.len() as f32;
+        let reference = &multi_channel[0];
+
+        let mut output = reference.clone();
+        let mut first_tau = None;
+
+        for channel in &multi_channel[1..] {
+            let tau = gcc_phat_delay(reference, channel);
+            first_tau.get_or_insert(tau);
+
+            let aligned = shift_samples(channel, tau);
+            for (i, &sample) in aligned.iter().enumerate() {
+                output[i] += sample;
             }
         }
-        
+
+        for sample in output.iter_mut() {
+            *sample /= multi_channel.len() as f32;
+        }
+
+        let doa_rad = first_tau.map(|tau| {
+            let arg = (tau as f32 * SOUND_SPEED_MPS) / (self.mic_spacing_m * self.sample_rate as f32);
+            arg.clamp(-1.0, 1.0).asin()
+        });
+
+        debug_assert_eq!(output.len(), len);
+        BeamformOutput {
+            samples: output,
+            doa_rad,
+        }
+    }
+
+    /// Single-channel spectral-subtraction denoising. `processor` gates each
+    /// overlapping frame through its VAD so only non-voice frames feed the
+    /// running noise magnitude estimate `|N|`; every frame is then cleaned
+    /// as `|S| = max(|Y| - alpha*|N|, floor_gain*|Y|)` keeping `Y`'s phase,
+    /// and reconstructed via windowed overlap-add.
+    pub fn denoise(&mut self, audio: &[f32], processor: &mut AudioProcessor) -> Vec<f32> {
+        if audio.len() < DENOISE_FRAME_SIZE {
+            return audio.to_vec();
+        }
+
+        let hop = DENOISE_FRAME_SIZE / 2;
+        let window = hann_window(DENOISE_FRAME_SIZE);
+        let floor_gain = db_to_gain(self.spectral_floor_db);
+
+        let mut planner = FftPlanner::<f32>::new();
+        let fft = planner.plan_fft_forward(DENOISE_FRAME_SIZE);
+        let ifft = planner.plan_fft_inverse(DENOISE_FRAME_SIZE);
+        let ifft_scale = 1.0 / DENOISE_FRAME_SIZE as f32;
+
+        let mut output = vec![0.0f32; audio.len()];
+        let mut coverage = vec![0.0f32; audio.len()];
+
+        let mut start = 0;
+        let mut last_frame_end = 0;
+        while start + DENOISE_FRAME_SIZE <= audio.len() {
+            let frame = &audio[start..start + DENOISE_FRAME_SIZE];
+            let is_voice = processor.detect_vad(frame, self.sample_rate).unwrap_or(true);
+
+            let mut spectrum: Vec<Complex<f32>> = frame
+                .iter()
+                .zip(window.iter())
+                .map(|(&s, &w)| Complex::new(s * w, 0.0))
+                .collect();
+            fft.process(&mut spectrum);
+
+            let noise_mag = self
+                .noise_mag
+                .get_or_insert_with(|| spectrum.iter().map(|c| c.norm()).collect());
+            if !is_voice {
+                for (n, y) in noise_mag.iter_mut().zip(spectrum.iter()) {
+                    *n = NOISE_EMA_ALPHA * y.norm() + (1.0 - NOISE_EMA_ALPHA) * *n;
+                }
+            }
+
+            let mut cleaned: Vec<Complex<f32>> = spectrum
+                .iter()
+                .zip(noise_mag.iter())
+                .map(|(y, &n)| {
+                    let mag_y = y.norm();
+                    if mag_y <= f32::EPSILON {
+                        return Complex::new(0.0, 0.0);
+                    }
+                    let mag_s = (mag_y - self.oversubtraction_alpha * n).max(floor_gain * mag_y);
+                    y * (mag_s / mag_y) // keep Y's phase, replace its magnitude
+                })
+                .collect();
+            ifft.process(&mut cleaned);
+
+            for (i, sample) in cleaned.iter().enumerate() {
+                output[start + i] += sample.re * ifft_scale * window[i];
+                coverage[start + i] += window[i] * window[i];
+            }
+
+            last_frame_end = start + DENOISE_FRAME_SIZE;
+            start += hop;
+        }
+
+        for (sample, &cov) in output.iter_mut().zip(coverage.iter()) {
+            if cov > 1e-6 {
+                *sample /= cov;
+            }
+        }
+        // Tail past the last full frame is untouched by overlap-add; pass it through.
+        output[last_frame_end..].copy_from_slice(&audio[last_frame_end..]);
         output
     }
-    
-    pub fn denoise(&self, audio: &[f32]) -> Vec<f32> {
-        // Spectral subtraction or Wiener filtering (placeholder)
-        audio.to_vec()
+}
+
+/// Estimate the signed inter-channel sample delay `tau` of `other` relative
+/// to `reference` via Generalized Cross-Correlation with Phase Transform
+/// (GCC-PHAT): cross-correlate in the frequency domain after whitening the
+/// cross-spectrum to unit magnitude, then take the lag of the correlation
+/// peak. A positive `tau` means `other` lags `reference` by `tau` samples.
+fn gcc_phat_delay(reference: &[f32], other: &[f32]) -> isize {
+    let n = reference.len().max(other.len()).max(1);
+    // Zero-pad past n so the circular correlation doesn't alias real lags.
+    let fft_len = (2 * n).next_power_of_two();
+
+    let mut planner = FftPlanner::<f32>::new();
+    let fft = planner.plan_fft_forward(fft_len);
+    let ifft = planner.plan_fft_inverse(fft_len);
+
+    let mut ref_buf = to_padded_complex(reference, fft_len);
+    let mut other_buf = to_padded_complex(other, fft_len);
+    fft.process(&mut ref_buf);
+    fft.process(&mut other_buf);
+
+    let mut cross: Vec<Complex<f32>> = ref_buf
+        .iter()
+        .zip(other_buf.iter())
+        .map(|(r, o)| {
+            let product = r * o.conj();
+            let magnitude = product.norm().max(PHAT_EPS);
+            product / magnitude
+        })
+        .collect();
+
+    ifft.process(&mut cross);
+
+    let (peak_idx, _) = cross
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.norm().partial_cmp(&b.norm()).unwrap())
+        .expect("fft_len is always > 0");
+
+    // The second half of the circular correlation holds negative lags. The
+    // IFFT(R * conj(O)) correlation peaks at the lag where shifting
+    // `reference` forward by that lag aligns it with `other`, which is the
+    // negation of `other`'s delay relative to `reference` - negate it so a
+    // positive result means `other` lags `reference`, matching this
+    // function's own docstring.
+    let lag = if peak_idx <= fft_len / 2 {
+        peak_idx as isize
+    } else {
+        peak_idx as isize - fft_len as isize
+    };
+    -lag
+}
+
+fn to_padded_complex(samples: &[f32], len: usize) -> Vec<Complex<f32>> {
+    let mut buf: Vec<Complex<f32>> = samples.iter().map(|&s| Complex::new(s, 0.0)).collect();
+    buf.resize(len, Complex::new(0.0, 0.0));
+    buf
+}
+
+/// Shift `channel` by `shift` samples (`out[n] = channel[n + shift]`),
+/// filling positions that fall outside the original buffer with silence.
+fn shift_samples(channel: &[f32], shift: isize) -> Vec<f32> {
+    let len = channel.len();
+    let mut out = vec![0.0f32; len];
+    for (i, slot) in out.iter_mut().enumerate() {
+        let src = i as isize + shift;
+        if src >= 0 && (src as usize) < len {
+            *slot = channel[src as usize];
+        }
     }
+    out
 }
 
 #[cfg(test)]
@@ -95,13 +347,120 @@ mod tests {
     #[test]
     fn test_beamforming() {
         let dsp = AudioDsp::new(4);
-        
+
         let channel1 = vec![1.0f32; 100];
         let channel2 = vec![0.5f32; 100];
         let channels = vec![channel1, channel2];
-        
-        let output = dsp.beamform(&channels);
-        assert_eq!(output.len(), 100);
-        assert!((output[0] - 0.75).abs() < 0.01);
+
+        let result = dsp.beamform(&channels);
+        assert_eq!(result.samples.len(), 100);
+        assert!((result.samples[0] - 0.75).abs() < 0.01);
+    }
+
+    fn sine_wave(freq_hz: f32, sample_rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * std::f32::consts::PI * freq_hz * i as f32 / sample_rate as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_gcc_phat_recovers_known_delay() {
+        let sample_rate = 16000;
+        let reference = sine_wave(440.0, sample_rate, 512);
+        let true_delay = 7isize;
+
+        // delayed[n] = reference[n - true_delay]
+        let mut delayed = vec![0.0f32; reference.len()];
+        for i in 0..delayed.len() {
+            let src = i as isize - true_delay;
+            if src >= 0 {
+                delayed[i] = reference[src as usize];
+            }
+        }
+
+        let tau = gcc_phat_delay(&reference, &delayed);
+        assert_eq!(tau, true_delay);
+    }
+
+    #[test]
+    fn test_beamform_reports_doa_for_delayed_source() {
+        let sample_rate = 16000;
+        let dsp = AudioDsp::with_geometry(2, 0.05, sample_rate);
+        let reference = sine_wave(440.0, sample_rate, 512);
+
+        let mut delayed = vec![0.0f32; reference.len()];
+        for i in 0..delayed.len() {
+            if i >= 2 {
+                delayed[i] = reference[i - 2];
+            }
+        }
+
+        let result = dsp.beamform(&vec![reference, delayed]);
+        assert!(result.doa_rad.is_some());
+        assert!(result.doa_rad.unwrap().abs() <= std::f32::consts::FRAC_PI_2);
+    }
+
+    #[test]
+    fn test_beamform_single_channel_passthrough() {
+        let dsp = AudioDsp::new(1);
+        let channel = vec![0.3f32; 64];
+        let result = dsp.beamform(&vec![channel.clone()]);
+        assert_eq!(result.samples, channel);
+        assert!(result.doa_rad.is_none());
+    }
+
+    fn energy(samples: &[f32]) -> f32 {
+        samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32
+    }
+
+    #[test]
+    fn test_denoise_suppresses_stationary_noise() {
+        let mut dsp = AudioDsp::new(1);
+        let mut processor = AudioProcessor::new(0.01);
+
+        // Deterministic pseudo-noise, low-energy enough to stay below the
+        // VAD threshold on every frame so the noise estimate tracks it fully.
+        let noise: Vec<f32> = (0..4096)
+            .map(|i| 0.01 * ((i as f32 * 12.9898).sin() * 43758.5453).fract())
+            .collect();
+
+        let denoised = dsp.denoise(&noise, &mut processor);
+        assert_eq!(denoised.len(), noise.len());
+        assert!(energy(&denoised) < energy(&noise));
+    }
+
+    #[test]
+    fn test_denoise_short_input_passes_through() {
+        let mut dsp = AudioDsp::new(1);
+        let mut processor = AudioProcessor::new(0.01);
+        let audio = vec![0.2f32; DENOISE_FRAME_SIZE - 1];
+
+        let denoised = dsp.denoise(&audio, &mut processor);
+        assert_eq!(denoised, audio);
+    }
+
+    #[test]
+    fn test_db_to_gain_matches_known_points() {
+        assert!((db_to_gain(0.0) - 1.0).abs() < 1e-6);
+        assert!((db_to_gain(-20.0) - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_detect_vad_raw_decodes_s16le_before_gating() {
+        let mut processor = AudioProcessor::new(0.01);
+
+        let silence_bytes = vec![0u8; 1024 * 2];
+        assert!(!processor
+            .detect_vad_raw(&silence_bytes, SampleFormat::S16Le, 1, 16000)
+            .unwrap());
+
+        let loud_sample: i16 = 10_000;
+        let voice_bytes: Vec<u8> = std::iter::repeat(loud_sample.to_le_bytes())
+            .take(1024)
+            .flatten()
+            .collect();
+        assert!(processor
+            .detect_vad_raw(&voice_bytes, SampleFormat::S16Le, 1, 16000)
+            .unwrap());
     }
 }
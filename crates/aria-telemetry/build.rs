@@ -0,0 +1,29 @@
+//! Compiles `schemas/*.proto` into a `FileDescriptorSet` and embeds it in the
+//! binary, so `SchemaRegistry` can resolve each `schema_id` to a concrete
+//! message descriptor at startup instead of trusting whatever name a caller
+//! passes to `register`.
+
+use std::path::{Path, PathBuf};
+
+fn main() {
+    let schemas_dir = Path::new("schemas");
+    let mut proto_files: Vec<PathBuf> = std::fs::read_dir(schemas_dir)
+        .expect("aria-telemetry/schemas/ directory must exist")
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "proto"))
+        .collect();
+    proto_files.sort();
+
+    let descriptor_path =
+        PathBuf::from(std::env::var("OUT_DIR").expect("OUT_DIR set by cargo")).join("file_descriptor_set.bin");
+
+    prost_build::Config::new()
+        .file_descriptor_set_path(&descriptor_path)
+        .compile_protos(&proto_files, &[schemas_dir])
+        .expect("failed to compile schemas/*.proto into a FileDescriptorSet");
+
+    for proto_file in &proto_files {
+        println!("cargo:rerun-if-changed={}", proto_file.display());
+    }
+}
@@ -3,7 +3,7 @@
 //! TX: rate limiting, jitter smoothing, conditioning
 //! RX: de-jitter, reordering, drift/doppler compensation, interference detection
 
-use aria_domain::{AriaResult, Envelope};
+use aria_domain::{AriaResult, ClockDuration, Envelope};
 use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 
@@ -46,10 +46,47 @@ impl TxConditioner {
     }
 }
 
+/// Starting playout delay before any inter-arrival jitter has been observed.
+fn default_playout_delay() -> ClockDuration {
+    ClockDuration::from_millis(20)
+}
+
+/// Multiple of the smoothed jitter estimate budgeted as playout delay, on
+/// top of the jitter itself — a single inter-arrival estimate is noisy, so
+/// the window needs headroom beyond the bare estimate to avoid spurious
+/// concealment.
+const JITTER_SAFETY_MARGIN: f64 = 4.0;
+/// Floor on the playout delay so a just-started, near-zero jitter estimate
+/// doesn't leave the window too tight to absorb normal reordering.
+const MIN_PLAYOUT_DELAY_SECS: f64 = 0.005;
+
+/// One slot emitted by `RxDeJitter::add`: either the packet that was
+/// actually waiting at that sequence number, or a concealment marker
+/// standing in for a packet that blew past the playout deadline.
+#[derive(Debug, Clone)]
+pub enum PlayoutItem {
+    Packet(Envelope),
+    Concealment(u64),
+}
+
 pub struct RxDeJitter {
     buffer: VecDeque<(u64, Envelope)>,
     buffer_size: usize,
     next_sequence: u64,
+    last_arrival: Option<Instant>,
+    /// Most recent inter-arrival gap, at full femtosecond resolution so
+    /// sub-millisecond jitter isn't quantized away before it can inform the
+    /// playout delay.
+    last_inter_arrival: Option<ClockDuration>,
+    /// Sender-side timestamp of the last packet seen, used to compute the
+    /// expected inter-packet spacing for the RFC 3550-style jitter estimator.
+    last_send_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    /// Smoothed jitter estimate, in seconds.
+    jitter_secs: f64,
+    playout_delay: ClockDuration,
+    /// When we started waiting on `next_sequence`; `None` while nothing is
+    /// outstanding.
+    waiting_since: Option<Instant>,
 }
 
 impl RxDeJitter {
@@ -58,63 +95,189 @@ impl RxDeJitter {
             buffer: VecDeque::new(),
             buffer_size,
             next_sequence: 0,
+            last_arrival: None,
+            last_inter_arrival: None,
+            last_send_timestamp: None,
+            jitter_secs: 0.0,
+            playout_delay: default_playout_delay(),
+            waiting_since: None,
         }
     }
-    
-    pub fn add(&mut self, envelope: Envelope) -> Vec<Envelope> {
+
+    /// The gap since the previous `add()` call, at full femtosecond
+    /// resolution.
+    pub fn last_inter_arrival(&self) -> Option<ClockDuration> {
+        self.last_inter_arrival
+    }
+
+    /// The RFC 3550-style smoothed inter-arrival jitter estimate.
+    pub fn jitter_estimate(&self) -> ClockDuration {
+        ClockDuration::from_secs_f64(self.jitter_secs)
+    }
+
+    /// The delay currently budgeted for reordering before playout.
+    pub fn playout_delay(&self) -> ClockDuration {
+        self.playout_delay
+    }
+
+    pub fn add(&mut self, envelope: Envelope) -> Vec<PlayoutItem> {
+        let now = Instant::now();
+        if let Some(last) = self.last_arrival {
+            let arrival_gap = now.duration_since(last);
+            self.last_inter_arrival = Some(arrival_gap.into());
+
+            if let Some(last_ts) = self.last_send_timestamp {
+                // RFC 3550 6.4.1: D = (Rj - Ri) - (Sj - Si); jitter smooths
+                // |D| with a gain of 1/16 per sample.
+                let expected_gap = chrono_duration_to_secs(envelope.timestamp - last_ts);
+                let d = arrival_gap.as_secs_f64() - expected_gap;
+                self.jitter_secs += (d.abs() - self.jitter_secs) / 16.0;
+                self.playout_delay = ClockDuration::from_secs_f64(
+                    (self.jitter_secs * JITTER_SAFETY_MARGIN).max(MIN_PLAYOUT_DELAY_SECS),
+                );
+            }
+        }
+        self.last_arrival = Some(now);
+        self.last_send_timestamp = Some(envelope.timestamp);
+
         let seq = envelope.metadata.sequence_number;
-        
+
         // Insert in order
         let pos = self.buffer.iter().position(|(s, _)| *s > seq).unwrap_or(self.buffer.len());
         self.buffer.insert(pos, (seq, envelope));
-        
+
         // Trim to size
         if self.buffer.len() > self.buffer_size {
             self.buffer.pop_front();
         }
-        
-        // Extract consecutive packets starting from next_sequence
+
+        if self.waiting_since.is_none() && !self.buffer.is_empty() {
+            self.waiting_since = Some(now);
+        }
+
+        // Extract consecutive packets starting from next_sequence; if the
+        // next one is overdue past the playout deadline, skip it with a
+        // concealment marker instead of blocking the whole stream.
         let mut output = Vec::new();
-        while let Some((seq, _)) = self.buffer.front() {
-            if *seq == self.next_sequence {
-                if let Some((_, env)) = self.buffer.pop_front() {
-                    output.push(env);
+        loop {
+            match self.buffer.front() {
+                Some((seq, _)) if *seq == self.next_sequence => {
+                    let (_, env) = self.buffer.pop_front().unwrap();
+                    output.push(PlayoutItem::Packet(env));
+                    self.next_sequence += 1;
+                    self.waiting_since = Some(now);
+                }
+                Some(_) => {
+                    let overdue = self
+                        .waiting_since
+                        .map(|since| now.duration_since(since) >= self.playout_delay.into())
+                        .unwrap_or(false);
+                    if !overdue {
+                        break;
+                    }
+                    output.push(PlayoutItem::Concealment(self.next_sequence));
                     self.next_sequence += 1;
                 }
-            } else {
-                break;
+                None => break,
             }
         }
-        
+
         output
     }
 }
 
+/// Default proportional gain of the clock-offset loop filter.
+const DEFAULT_KP: f64 = 0.5;
+/// Default integral gain (how fast the drift-rate estimate is allowed to move).
+const DEFAULT_KI: f64 = 0.05;
+/// Default outlier gate: a measured offset predicting more than this far from
+/// the current phase/frequency estimate is dropped rather than fed to the loop.
+const DEFAULT_OUTLIER_GATE: Duration = Duration::from_millis(500);
+/// Default clamp on the drift-rate estimate, in parts-per-million, so a
+/// single bad sample can't run the frequency term away.
+const DEFAULT_MAX_DRIFT_PPM: f64 = 500.0;
+
+fn chrono_duration_to_secs(d: chrono::Duration) -> f64 {
+    d.num_nanoseconds().unwrap_or(i64::MAX) as f64 / 1e9
+}
+
+/// Digital PLL-style clock discipline: tracks a phase estimate `theta` (the
+/// current clock offset) and a frequency estimate `drift_rate` (offset drift
+/// per second) via a proportional-integral loop filter, so `compensate` can
+/// extrapolate drift between measurements instead of freezing the last
+/// offset seen.
 pub struct DriftCompensator {
-    clock_offset: Duration,
+    theta: f64,
     drift_rate: f64,
+    last_update: Option<chrono::DateTime<chrono::Utc>>,
+    kp: f64,
+    ki: f64,
+    outlier_gate: Duration,
+    max_drift_ppm: f64,
 }
 
 impl DriftCompensator {
     pub fn new() -> Self {
+        Self::with_params(DEFAULT_KP, DEFAULT_KI, DEFAULT_OUTLIER_GATE, DEFAULT_MAX_DRIFT_PPM)
+    }
+
+    pub fn with_params(kp: f64, ki: f64, outlier_gate: Duration, max_drift_ppm: f64) -> Self {
         Self {
-            clock_offset: Duration::ZERO,
+            theta: 0.0,
             drift_rate: 0.0,
+            last_update: None,
+            kp,
+            ki,
+            outlier_gate,
+            max_drift_ppm,
         }
     }
-    
+
+    /// Extrapolate the clock offset out to `timestamp` using the current
+    /// phase and frequency estimates, rather than applying a frozen offset.
+    ///
+    /// The offset is routed through `ClockDuration` so sub-millisecond
+    /// drift correction survives instead of being quantized away; only the
+    /// sign is carried separately, since `ClockDuration` itself is unsigned.
     pub fn compensate(&self, timestamp: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
-        // Apply clock offset and drift compensation
-        timestamp + chrono::Duration::from_std(self.clock_offset).unwrap_or_default()
+        let offset = match self.last_update {
+            Some(last) => self.theta + self.drift_rate * chrono_duration_to_secs(timestamp - last),
+            None => self.theta,
+        };
+        let magnitude = ClockDuration::from_secs_f64(offset.abs());
+        let delta: chrono::Duration = magnitude.into();
+        timestamp + if offset < 0.0 { -delta } else { delta }
     }
-    
-    pub fn update_offset(&mut self, measured_offset: Duration) {
-        // Exponential moving average
-        let alpha = 0.1;
-        let current_ms = self.clock_offset.as_millis() as f64;
-        let measured_ms = measured_offset.as_millis() as f64;
-        let new_ms = alpha * measured_ms + (1.0 - alpha) * current_ms;
-        self.clock_offset = Duration::from_millis(new_ms as u64);
+
+    /// Feed a freshly measured clock offset into the loop filter. The first
+    /// measurement seeds `theta` directly; later ones compute a prediction
+    /// error against the current phase/frequency estimate and use it to
+    /// correct both, gating outliers and clamping the drift-rate estimate.
+    pub fn update_offset(&mut self, measured_offset: chrono::Duration) {
+        let e_raw = chrono_duration_to_secs(measured_offset);
+        let now = chrono::Utc::now();
+
+        let last = match self.last_update {
+            None => {
+                self.theta = e_raw;
+                self.last_update = Some(now);
+                return;
+            }
+            Some(last) => last,
+        };
+
+        let dt = chrono_duration_to_secs(now - last);
+        let predicted = self.theta + self.drift_rate * dt;
+        let e = e_raw - predicted;
+
+        if e.abs() > chrono_duration_to_secs(self.outlier_gate) {
+            return; // Outlier: leave theta/drift_rate/last_update untouched.
+        }
+
+        let max_drift = self.max_drift_ppm * 1e-6;
+        self.drift_rate = (self.drift_rate + self.ki * e).clamp(-max_drift, max_drift);
+        self.theta += self.kp * e + self.drift_rate * dt;
+        self.last_update = Some(now);
     }
 }
 
@@ -136,10 +299,12 @@ mod tests {
             metadata: EnvelopeMetadata {
                 source_node: "test".into(),
                 sequence_number: seq,
+                group_id: None,
                 fragment_info: None,
                 fec_info: None,
                 crypto_info: None,
                 qos_class: "default".into(),
+                codec: CodecKind::Protobuf,
             },
         }
     }
@@ -156,24 +321,114 @@ mod tests {
     #[test]
     fn test_rx_dejitter_ordering() {
         let mut dejitter = RxDeJitter::new(10);
-        
+
         // Receive out of order
         dejitter.add(make_envelope(2));
         dejitter.add(make_envelope(0));
         dejitter.add(make_envelope(1));
-        
+
         let output = dejitter.add(make_envelope(3));
         // Should output 0, 1, 2, 3 in order
         assert_eq!(output.len(), 4);
     }
-    
+
+    #[test]
+    fn test_rx_dejitter_has_no_inter_arrival_before_second_packet() {
+        let mut dejitter = RxDeJitter::new(10);
+        assert!(dejitter.last_inter_arrival().is_none());
+        dejitter.add(make_envelope(0));
+        assert!(dejitter.last_inter_arrival().is_none());
+    }
+
+    #[test]
+    fn test_rx_dejitter_tracks_inter_arrival_gap() {
+        let mut dejitter = RxDeJitter::new(10);
+        dejitter.add(make_envelope(0));
+        std::thread::sleep(Duration::from_millis(5));
+        dejitter.add(make_envelope(1));
+
+        let gap = dejitter.last_inter_arrival().expect("gap after second packet");
+        assert!(gap.as_secs_f64() > 0.0);
+    }
+
+    #[test]
+    fn test_rx_dejitter_starts_with_default_playout_delay() {
+        let dejitter = RxDeJitter::new(10);
+        assert_eq!(dejitter.playout_delay(), default_playout_delay());
+    }
+
+    #[test]
+    fn test_rx_dejitter_conceals_instead_of_blocking_forever() {
+        let mut dejitter = RxDeJitter::new(10);
+        // Packet 1 never arrives. Tighten the playout window first so the
+        // test doesn't have to sleep for the 20ms default.
+        dejitter.add(make_envelope(0));
+        // Force the estimator's window down near the floor by feeding it a
+        // run of evenly-spaced packets before the gap.
+        for seq in 2..6 {
+            dejitter.add(make_envelope(seq));
+        }
+        std::thread::sleep(dejitter.playout_delay().into());
+        let output = dejitter.add(make_envelope(6));
+
+        assert!(output.iter().any(|item| matches!(item, PlayoutItem::Concealment(1))));
+        assert!(output.iter().any(|item| matches!(item, PlayoutItem::Packet(e) if e.metadata.sequence_number == 6)));
+    }
+
+    #[test]
+    fn test_rx_dejitter_jitter_estimate_starts_at_zero() {
+        let dejitter = RxDeJitter::new(10);
+        assert_eq!(dejitter.jitter_estimate(), ClockDuration::ZERO);
+    }
+
     #[test]
     fn test_drift_compensator() {
         let mut compensator = DriftCompensator::new();
-        compensator.update_offset(Duration::from_millis(100));
-        
+        compensator.update_offset(chrono::Duration::milliseconds(100));
+
         let timestamp = Utc::now();
         let compensated = compensator.compensate(timestamp);
         assert!(compensated > timestamp);
     }
+
+    #[test]
+    fn test_drift_compensator_converges_toward_consistent_offset() {
+        let mut compensator = DriftCompensator::new();
+        for _ in 0..20 {
+            compensator.update_offset(chrono::Duration::milliseconds(50));
+        }
+
+        let timestamp = Utc::now();
+        let compensated = compensator.compensate(timestamp);
+        let applied_ms = (compensated - timestamp).num_milliseconds();
+        assert!((applied_ms - 50).abs() < 10);
+    }
+
+    #[test]
+    fn test_drift_compensator_handles_negative_offset() {
+        let mut compensator = DriftCompensator::new();
+        compensator.update_offset(chrono::Duration::milliseconds(-50));
+
+        let timestamp = Utc::now();
+        let compensated = compensator.compensate(timestamp);
+        assert!(compensated < timestamp);
+    }
+
+    #[test]
+    fn test_drift_compensator_gates_outliers() {
+        let mut compensator = DriftCompensator::with_params(
+            DEFAULT_KP,
+            DEFAULT_KI,
+            Duration::from_millis(5),
+            DEFAULT_MAX_DRIFT_PPM,
+        );
+        compensator.update_offset(chrono::Duration::milliseconds(10));
+        // Wildly inconsistent with the seeded offset; should be gated.
+        compensator.update_offset(chrono::Duration::seconds(10));
+
+        let timestamp = Utc::now();
+        let compensated = compensator.compensate(timestamp);
+        let applied_ms = (compensated - timestamp).num_milliseconds();
+        assert!((applied_ms - 10).abs() < 5);
+    }
 }
@@ -1,18 +1,235 @@
 //! Cryptography: sign-then-encrypt (TX), verify-then-decrypt (RX)
 
+use argon2::Argon2;
 use aria_domain::{AriaError, AriaResult, ICryptoBox};
 use chacha20poly1305::{
     aead::{Aead, KeyInit, Payload},
     ChaCha20Poly1305, Nonce,
 };
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use hkdf::Hkdf;
 use rand::rngs::OsRng;
+use sha2::Sha256;
+use std::time::{Duration, Instant};
+use x25519_dalek::{EphemeralSecret, PublicKey as X25519PublicKey};
+
+/// Domain-separation context for the handshake's HKDF expand step; folded in
+/// alongside `key_id` so a session key can never be confused with a key
+/// derived for an unrelated purpose from the same ECDH shared secret.
+const HANDSHAKE_INFO_PREFIX: &[u8] = b"aria-cryptobox-handshake-v1:";
+
+/// How close to `u64::MAX` `encrypt_next`'s sequence counter can get before
+/// `rekey_status` reports `SequenceNearExhaustion`, so a rekey has room to
+/// happen well before the counter could ever actually wrap and force nonce
+/// reuse.
+const REKEY_EXHAUSTION_MARGIN: u64 = 1_000;
+
+/// Why `rekey_status`/`encrypt_next` flagged that this session's key should
+/// be rotated via a fresh handshake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RekeyReason {
+    MessageCount,
+    ByteCount,
+    Age,
+    SequenceNearExhaustion,
+}
+
+/// Thresholds after which a session key is due for rotation - whichever is
+/// hit first. There's no "right" default for every deployment, so these are
+/// generous, conservative limits rather than a claim about the AEAD's actual
+/// safe usage margin.
+#[derive(Debug, Clone, Copy)]
+pub struct RekeyPolicy {
+    pub max_messages: u64,
+    pub max_bytes: u64,
+    pub max_age: Duration,
+}
+
+impl Default for RekeyPolicy {
+    fn default() -> Self {
+        Self {
+            max_messages: 1_000_000,
+            max_bytes: 1 << 30,
+            max_age: Duration::from_secs(3600),
+        }
+    }
+}
+
+/// Receive-side replay/reorder guard: a 64-bit sliding bitmap anchored at
+/// the highest sequence number accepted so far. Tolerates out-of-order
+/// delivery within the last 64 sequences while making replay of anything
+/// older, or of a sequence already seen, impossible.
+struct ReplayWindow {
+    highest_accepted: Option<u64>,
+    bitmap: u64,
+}
+
+impl ReplayWindow {
+    fn new() -> Self {
+        Self { highest_accepted: None, bitmap: 0 }
+    }
+
+    /// Accepts `seq` and slides/marks the window, or rejects it as a replay.
+    fn accept(&mut self, seq: u64) -> AriaResult<()> {
+        let Some(highest) = self.highest_accepted else {
+            self.highest_accepted = Some(seq);
+            self.bitmap = 1;
+            return Ok(());
+        };
+
+        if seq > highest {
+            let advance = seq - highest;
+            self.bitmap = if advance >= 64 { 0 } else { self.bitmap << advance };
+            self.bitmap |= 1;
+            self.highest_accepted = Some(seq);
+            return Ok(());
+        }
+
+        let age = highest - seq;
+        if age >= 64 {
+            return Err(AriaError::Crypto("replay".into()));
+        }
+        let bit = 1u64 << age;
+        if self.bitmap & bit != 0 {
+            return Err(AriaError::Crypto("replay".into()));
+        }
+        self.bitmap |= bit;
+        Ok(())
+    }
+}
+
+/// Which end of a handshake-derived session produced a given nonce. A
+/// handshake's initiator and responder `CryptoBox`es hold the *identical*
+/// symmetric key (see `derive_session_cipher`), so without this the two
+/// sides' independently-counting-from-zero `seq_counter`s would collide on
+/// the same nonce under the same key the moment a session is used in both
+/// directions - catastrophic for an AEAD. Folding the role into the nonce
+/// instead partitions the nonce space in two, so each side's sequence
+/// counter only ever has to stay unique against itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceOrigin {
+    Initiator,
+    Responder,
+}
+
+impl NonceOrigin {
+    fn marker(self) -> u8 {
+        match self {
+            NonceOrigin::Initiator => 0x00,
+            NonceOrigin::Responder => 0x01,
+        }
+    }
+}
+
+/// Derives a 12-byte AEAD nonce from a 64-bit sequence number and the role
+/// that produced it: a one-byte origin marker, zero-padded high bytes, then
+/// the big-endian counter - so nonces never repeat as long as `seq` doesn't
+/// repeat *for that role*, even when both roles share a session key.
+fn nonce_for_sequence(seq: u64, origin: NonceOrigin) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[0] = origin.marker();
+    nonce[4..].copy_from_slice(&seq.to_be_bytes());
+    nonce
+}
+
+/// Fixed domain-separation salt for shared-secret identity derivation.
+/// Deliberately not random: every node stretching the *same* passphrase
+/// must land on the *same* seed, which a random salt would defeat.
+const SHARED_SECRET_ARGON2_SALT: &[u8] = b"aria-keymanager-shared-secret-v1";
+const SHARED_SECRET_HKDF_INFO: &[u8] = b"aria-shared-secret-identity-v1";
+
+/// Stretches `secret` into a deterministic Ed25519 signing key: Argon2id
+/// first (so a weak/short passphrase costs an attacker real work to guess),
+/// then HKDF-SHA256 to whiten the output into a key-sized, domain-separated
+/// seed. Every node given the same `secret` derives the same identity.
+fn signing_key_from_shared_secret(secret: &[u8]) -> AriaResult<SigningKey> {
+    let mut stretched = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(secret, SHARED_SECRET_ARGON2_SALT, &mut stretched)
+        .map_err(|e| AriaError::Crypto(format!("Argon2id key stretching failed: {e}")))?;
+
+    let hk = Hkdf::<Sha256>::new(None, &stretched);
+    let mut seed = [0u8; 32];
+    hk.expand(SHARED_SECRET_HKDF_INFO, &mut seed)
+        .map_err(|e| AriaError::Crypto(format!("HKDF expand failed: {e}")))?;
+
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// An ephemeral X25519 public key, signed by the sender's long-term Ed25519
+/// identity so the peer can trust it wasn't substituted in transit. Carries
+/// the signer's `verifying_key` so the peer can check the signature without
+/// already knowing who it's talking to - trusting that key is out of scope
+/// here and is the chunk4-3 trust store's job.
+pub struct HandshakeMsg {
+    pub verifying_key: VerifyingKey,
+    pub ephemeral_public_key: [u8; 32],
+    pub signature: Vec<u8>,
+}
+
+/// Verifies `msg.signature` over `msg.ephemeral_public_key` under
+/// `msg.verifying_key` and decodes it as an X25519 point.
+fn verify_ephemeral(msg: &HandshakeMsg) -> AriaResult<X25519PublicKey> {
+    let signature = Signature::from_slice(&msg.signature)
+        .map_err(|e| AriaError::Crypto(format!("malformed handshake signature: {e}")))?;
+    msg.verifying_key
+        .verify(&msg.ephemeral_public_key, &signature)
+        .map_err(|_| AriaError::Crypto("handshake signature verification failed".into()))?;
+    Ok(X25519PublicKey::from(msg.ephemeral_public_key))
+}
+
+/// Derives the session ChaCha20-Poly1305 key via HKDF-SHA256 over the ECDH
+/// shared secret, salted with the concatenated `initiator || responder`
+/// ephemeral public keys so both sides land on the same key regardless of
+/// which one they computed locally.
+fn derive_session_cipher(
+    shared_secret: &x25519_dalek::SharedSecret,
+    initiator_ephemeral: &X25519PublicKey,
+    responder_ephemeral: &X25519PublicKey,
+    key_id: &str,
+) -> AriaResult<ChaCha20Poly1305> {
+    let mut salt = Vec::with_capacity(64);
+    salt.extend_from_slice(initiator_ephemeral.as_bytes());
+    salt.extend_from_slice(responder_ephemeral.as_bytes());
+
+    let mut info = HANDSHAKE_INFO_PREFIX.to_vec();
+    info.extend_from_slice(key_id.as_bytes());
+
+    let hk = Hkdf::<Sha256>::new(Some(&salt), shared_secret.as_bytes());
+    let mut session_key = [0u8; 32];
+    hk.expand(&info, &mut session_key)
+        .map_err(|e| AriaError::Crypto(format!("HKDF expand failed: {e}")))?;
+    Ok(ChaCha20Poly1305::new((&session_key).into()))
+}
 
 pub struct CryptoBox {
     signing_key: SigningKey,
     verifying_key: VerifyingKey,
     cipher: ChaCha20Poly1305,
     key_id: String,
+    /// Ephemeral secret from the most recent `initiate_handshake` call,
+    /// held until `complete_handshake` consumes it to derive the session
+    /// cipher. `None` if no handshake is in flight.
+    pending_ephemeral: Option<(EphemeralSecret, X25519PublicKey)>,
+
+    /// Which role this box played when its cipher was derived. `new`/
+    /// `from_keys` build a cipher nobody else holds, so the choice here is
+    /// arbitrary for them; for a handshake-derived box it's what lets
+    /// `encrypt_next`/`decrypt_received` partition the nonce space against
+    /// the peer box sharing the same key (see `NonceOrigin`).
+    origin: NonceOrigin,
+
+    /// Send-side: next sequence number `encrypt_next` will use to derive
+    /// its nonce. Monotonically increasing, never reused.
+    seq_counter: u64,
+    /// Send-side: total plaintext bytes passed to `encrypt_next` so far.
+    bytes_encrypted: u64,
+    /// Send-side: when this session's key came into use, for the
+    /// `max_age` rekey threshold.
+    session_started_at: Instant,
+    rekey_policy: RekeyPolicy,
+    /// Receive-side replay/reorder guard for `decrypt_received`.
+    replay_window: ReplayWindow,
 }
 
 impl CryptoBox {
@@ -20,30 +237,213 @@ impl CryptoBox {
         let mut csprng = OsRng;
         let signing_key = SigningKey::generate(&mut csprng);
         let verifying_key = signing_key.verifying_key();
-        
+
         // Generate ChaCha20-Poly1305 key
         let cipher_key = ChaCha20Poly1305::generate_key(&mut OsRng);
         let cipher = ChaCha20Poly1305::new(&cipher_key);
-        
+
         Self {
             signing_key,
             verifying_key,
             cipher,
             key_id,
+            pending_ephemeral: None,
+            origin: NonceOrigin::Initiator,
+            seq_counter: 0,
+            bytes_encrypted: 0,
+            session_started_at: Instant::now(),
+            rekey_policy: RekeyPolicy::default(),
+            replay_window: ReplayWindow::new(),
         }
     }
-    
+
+    /// Defaults the new box to `NonceOrigin::Initiator`. Only safe to use
+    /// as-is when `cipher_key` isn't shared with another `CryptoBox` that
+    /// also sends traffic - otherwise both sides derive the same nonce
+    /// sequence under the same key. A duplex session sharing one
+    /// `cipher_key` between two senders must use `from_keys_with_origin`
+    /// and assign each side the opposite role.
     pub fn from_keys(signing_key: SigningKey, cipher_key: &[u8; 32], key_id: String) -> Self {
+        Self::from_keys_with_origin(signing_key, cipher_key, key_id, NonceOrigin::Initiator)
+    }
+
+    /// Same as `from_keys`, but lets the caller pick which side of a shared
+    /// `cipher_key` this box plays. Two peers that share a `cipher_key` and
+    /// both send traffic must pass opposite `NonceOrigin`s here - otherwise
+    /// `nonce_for_sequence` produces colliding nonces under the same AEAD
+    /// key the moment both sides encrypt.
+    pub fn from_keys_with_origin(
+        signing_key: SigningKey,
+        cipher_key: &[u8; 32],
+        key_id: String,
+        origin: NonceOrigin,
+    ) -> Self {
         let verifying_key = signing_key.verifying_key();
         let cipher = ChaCha20Poly1305::new(cipher_key.into());
-        
+
         Self {
             signing_key,
             verifying_key,
             cipher,
             key_id,
+            pending_ephemeral: None,
+            origin,
+            seq_counter: 0,
+            bytes_encrypted: 0,
+            session_started_at: Instant::now(),
+            rekey_policy: RekeyPolicy::default(),
+            replay_window: ReplayWindow::new(),
+        }
+    }
+
+    /// Overrides the default rekey thresholds for this session.
+    pub fn set_rekey_policy(&mut self, policy: RekeyPolicy) {
+        self.rekey_policy = policy;
+    }
+
+    /// Initiator side of the handshake: generates a fresh ephemeral X25519
+    /// keypair, signs the public half with this node's long-term Ed25519
+    /// identity, and holds the secret half pending `complete_handshake`.
+    pub fn initiate_handshake(&mut self) -> HandshakeMsg {
+        let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+        let ephemeral_public = X25519PublicKey::from(&ephemeral_secret);
+        let signature = self.signing_key.sign(ephemeral_public.as_bytes());
+
+        self.pending_ephemeral = Some((ephemeral_secret, ephemeral_public));
+
+        HandshakeMsg {
+            verifying_key: self.verifying_key,
+            ephemeral_public_key: *ephemeral_public.as_bytes(),
+            signature: signature.to_bytes().to_vec(),
         }
     }
+
+    /// Responder side: verifies the initiator's signed ephemeral key,
+    /// generates its own ephemeral keypair, and derives the session cipher
+    /// immediately. Returns a ready-to-use `CryptoBox` plus the reply to
+    /// send back to the initiator.
+    pub fn respond_handshake(&self, msg: &HandshakeMsg) -> AriaResult<(CryptoBox, HandshakeMsg)> {
+        let initiator_ephemeral = verify_ephemeral(msg)?;
+
+        let our_ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+        let our_ephemeral_public = X25519PublicKey::from(&our_ephemeral_secret);
+        let our_signature = self.signing_key.sign(our_ephemeral_public.as_bytes());
+
+        let shared_secret = our_ephemeral_secret.diffie_hellman(&initiator_ephemeral);
+        let cipher = derive_session_cipher(&shared_secret, &initiator_ephemeral, &our_ephemeral_public, &self.key_id)?;
+
+        let session_box = CryptoBox {
+            signing_key: self.signing_key.clone(),
+            verifying_key: self.verifying_key,
+            cipher,
+            key_id: self.key_id.clone(),
+            pending_ephemeral: None,
+            origin: NonceOrigin::Responder,
+            seq_counter: 0,
+            bytes_encrypted: 0,
+            session_started_at: Instant::now(),
+            rekey_policy: RekeyPolicy::default(),
+            replay_window: ReplayWindow::new(),
+        };
+        let reply = HandshakeMsg {
+            verifying_key: self.verifying_key,
+            ephemeral_public_key: *our_ephemeral_public.as_bytes(),
+            signature: our_signature.to_bytes().to_vec(),
+        };
+
+        Ok((session_box, reply))
+    }
+
+    /// Initiator side, part two: verifies the responder's signed ephemeral
+    /// key, computes the same ECDH shared secret, and returns the finished
+    /// session `CryptoBox`. Errors if no `initiate_handshake` is pending.
+    pub fn complete_handshake(&mut self, msg: &HandshakeMsg) -> AriaResult<CryptoBox> {
+        let responder_ephemeral = verify_ephemeral(msg)?;
+        let (our_ephemeral_secret, our_ephemeral_public) = self
+            .pending_ephemeral
+            .take()
+            .ok_or_else(|| AriaError::Crypto("complete_handshake called with no pending initiate_handshake".into()))?;
+
+        let shared_secret = our_ephemeral_secret.diffie_hellman(&responder_ephemeral);
+        let cipher = derive_session_cipher(&shared_secret, &our_ephemeral_public, &responder_ephemeral, &self.key_id)?;
+
+        Ok(CryptoBox {
+            signing_key: self.signing_key.clone(),
+            verifying_key: self.verifying_key,
+            cipher,
+            key_id: self.key_id.clone(),
+            pending_ephemeral: None,
+            origin: NonceOrigin::Initiator,
+            seq_counter: 0,
+            bytes_encrypted: 0,
+            session_started_at: Instant::now(),
+            rekey_policy: RekeyPolicy::default(),
+            replay_window: ReplayWindow::new(),
+        })
+    }
+
+    /// Encrypts `data` under this session's cipher, deriving the AEAD nonce
+    /// from an internally-owned sequence counter instead of trusting the
+    /// caller for one - the same nonce is never reused for two different
+    /// messages. Returns the sequence number the receiver needs to derive
+    /// the matching nonce and feed its replay window via
+    /// `decrypt_received`, the ciphertext, and `Some` reason if a rekey
+    /// threshold has now been crossed.
+    pub fn encrypt_next(&mut self, data: &[u8]) -> AriaResult<(u64, Vec<u8>, Option<RekeyReason>)> {
+        if self.seq_counter == u64::MAX {
+            return Err(AriaError::Crypto("sequence counter exhausted; rekey required".into()));
+        }
+
+        let seq = self.seq_counter;
+        let nonce = nonce_for_sequence(seq, self.origin);
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), data)
+            .map_err(|e| AriaError::Crypto(format!("Encryption failed: {e}")))?;
+
+        self.seq_counter += 1;
+        self.bytes_encrypted += data.len() as u64;
+
+        Ok((seq, ciphertext, self.rekey_status()))
+    }
+
+    /// Verifies `seq` against the replay/reorder window, then decrypts
+    /// `ciphertext` using the nonce derived from `seq` and the *peer's*
+    /// origin - `seq` was assigned by the other side's `encrypt_next`, so
+    /// its nonce was built from the opposite role marker to this box's own.
+    /// Rejects duplicate or too-old sequences with
+    /// `AriaError::Crypto("replay")` before the AEAD ever runs.
+    pub fn decrypt_received(&mut self, seq: u64, ciphertext: &[u8]) -> AriaResult<Vec<u8>> {
+        self.replay_window.accept(seq)?;
+
+        let peer_origin = match self.origin {
+            NonceOrigin::Initiator => NonceOrigin::Responder,
+            NonceOrigin::Responder => NonceOrigin::Initiator,
+        };
+        let nonce = nonce_for_sequence(seq, peer_origin);
+        self.cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext)
+            .map_err(|e| AriaError::Crypto(format!("Decryption failed: {e}")))
+    }
+
+    /// Checks the send-side counters against `rekey_policy` without
+    /// encrypting anything. `encrypt_next` calls this after every message;
+    /// exposed separately so a caller can poll it between messages too.
+    pub fn rekey_status(&self) -> Option<RekeyReason> {
+        if self.seq_counter >= u64::MAX - REKEY_EXHAUSTION_MARGIN {
+            return Some(RekeyReason::SequenceNearExhaustion);
+        }
+        if self.seq_counter >= self.rekey_policy.max_messages {
+            return Some(RekeyReason::MessageCount);
+        }
+        if self.bytes_encrypted >= self.rekey_policy.max_bytes {
+            return Some(RekeyReason::ByteCount);
+        }
+        if self.session_started_at.elapsed() >= self.rekey_policy.max_age {
+            return Some(RekeyReason::Age);
+        }
+        None
+    }
 }
 
 impl ICryptoBox for CryptoBox {
@@ -80,10 +480,14 @@ impl ICryptoBox for CryptoBox {
     }
 }
 
-/// Key manager for rotation and multi-key support
+/// Key manager for rotation, multi-key support, and peer trust. Doubles as
+/// the node's trust store: `trusted_peers` holds the `VerifyingKey` each
+/// known peer signs with, so `verify_from_any` can authenticate a message
+/// without knowing in advance which peer sent it.
 pub struct KeyManager {
     active_key_id: String,
     keys: std::collections::HashMap<String, CryptoBox>,
+    trusted_peers: std::collections::HashMap<String, VerifyingKey>,
 }
 
 impl KeyManager {
@@ -91,29 +495,72 @@ impl KeyManager {
         Self {
             active_key_id: String::new(),
             keys: std::collections::HashMap::new(),
+            trusted_peers: std::collections::HashMap::new(),
         }
     }
-    
+
+    /// Shared-secret provisioning mode: stretches `secret` into a
+    /// deterministic Ed25519 identity, registers it as this node's active
+    /// key, and trusts it under the `"shared-secret"` peer id - since every
+    /// node configured with the same secret derives the same identity, they
+    /// all trust each other by construction with no explicit
+    /// `add_trusted_peer` call needed.
+    pub fn from_shared_secret(secret: &[u8]) -> AriaResult<Self> {
+        let signing_key = signing_key_from_shared_secret(secret)?;
+        let verifying_key = signing_key.verifying_key();
+        let cipher_key: [u8; 32] = rand::random();
+        let key_id = "shared-secret".to_string();
+
+        let mut manager = Self::new();
+        manager.add_key(key_id.clone(), CryptoBox::from_keys(signing_key, &cipher_key, key_id.clone()));
+        manager.add_trusted_peer(key_id, verifying_key);
+        Ok(manager)
+    }
+
     pub fn add_key(&mut self, key_id: String, crypto_box: CryptoBox) {
         if self.active_key_id.is_empty() {
             self.active_key_id = key_id.clone();
         }
         self.keys.insert(key_id, crypto_box);
     }
-    
+
     pub fn get_active_key(&self) -> Option<&CryptoBox> {
         self.keys.get(&self.active_key_id)
     }
-    
+
     pub fn get_key(&self, key_id: &str) -> Option<&CryptoBox> {
         self.keys.get(key_id)
     }
-    
+
     pub fn rotate(&mut self, new_key_id: String) {
         if self.keys.contains_key(&new_key_id) {
             self.active_key_id = new_key_id;
         }
     }
+
+    /// Explicit-trust mode: registers `node_id`'s public key so
+    /// `verify_from_any` can authenticate messages signed by it.
+    pub fn add_trusted_peer(&mut self, node_id: String, key: VerifyingKey) {
+        self.trusted_peers.insert(node_id, key);
+    }
+
+    /// Looks up a trusted peer's verifying key by node id, for callers that
+    /// already know who they expect a message from (e.g. an envelope's
+    /// `source_node`) instead of needing `verify_from_any`'s search.
+    pub fn trusted_peer(&self, node_id: &str) -> Option<&VerifyingKey> {
+        self.trusted_peers.get(node_id)
+    }
+
+    /// Tries `signature` against every trusted peer's key and returns the
+    /// id of whichever one validates, or `None` if no trusted peer signed
+    /// `data`.
+    pub fn verify_from_any(&self, data: &[u8], signature: &[u8]) -> Option<String> {
+        let sig = Signature::from_slice(signature).ok()?;
+        self.trusted_peers
+            .iter()
+            .find(|(_, key)| key.verify(data, &sig).is_ok())
+            .map(|(node_id, _)| node_id.clone())
+    }
 }
 
 #[cfg(test)]
@@ -172,4 +619,220 @@ mod tests {
         manager.rotate("key2".into());
         assert_eq!(manager.get_active_key().unwrap().key_id(), "key2");
     }
+
+    #[test]
+    fn test_explicit_trust_verifies_a_registered_peer() {
+        let peer = CryptoBox::new("peer-1".into());
+        let mut manager = KeyManager::new();
+        manager.add_trusted_peer("peer-1".into(), peer.verifying_key);
+
+        let data = b"forward 2.0 m/s";
+        let signature = peer.sign(data).unwrap();
+
+        assert_eq!(manager.verify_from_any(data, &signature), Some("peer-1".into()));
+    }
+
+    #[test]
+    fn test_trusted_peer_looks_up_a_registered_peer_by_node_id() {
+        let peer = CryptoBox::new("peer-1".into());
+        let mut manager = KeyManager::new();
+        manager.add_trusted_peer("peer-1".into(), peer.verifying_key);
+
+        assert_eq!(manager.trusted_peer("peer-1"), Some(&peer.verifying_key));
+        assert_eq!(manager.trusted_peer("peer-2"), None);
+    }
+
+    #[test]
+    fn test_verify_from_any_rejects_an_untrusted_signer() {
+        let stranger = CryptoBox::new("stranger".into());
+        let manager = KeyManager::new();
+
+        let data = b"forward 2.0 m/s";
+        let signature = stranger.sign(data).unwrap();
+
+        assert_eq!(manager.verify_from_any(data, &signature), None);
+    }
+
+    #[test]
+    fn test_shared_secret_nodes_derive_the_same_identity_and_trust_it() {
+        let secret = b"correct horse battery staple";
+        let node_a = KeyManager::from_shared_secret(secret).unwrap();
+        let node_b = KeyManager::from_shared_secret(secret).unwrap();
+
+        let data = b"forward 2.0 m/s";
+        let signature = node_a.get_active_key().unwrap().sign(data).unwrap();
+
+        // node_b never saw node_a's key explicitly - it trusts the identity
+        // the shared secret deterministically derives.
+        assert_eq!(node_b.verify_from_any(data, &signature), Some("shared-secret".into()));
+    }
+
+    #[test]
+    fn test_different_shared_secrets_derive_different_identities() {
+        let node_a = KeyManager::from_shared_secret(b"secret-one").unwrap();
+        let node_b = KeyManager::from_shared_secret(b"secret-two").unwrap();
+
+        let data = b"forward 2.0 m/s";
+        let signature = node_a.get_active_key().unwrap().sign(data).unwrap();
+
+        assert_eq!(node_b.verify_from_any(data, &signature), None);
+    }
+
+    #[test]
+    fn test_handshake_derives_matching_session_cipher() {
+        let mut initiator = CryptoBox::new("session-1".into());
+        let responder = CryptoBox::new("session-1".into());
+
+        let initiator_msg = initiator.initiate_handshake();
+        let (responder_session, responder_msg) = responder.respond_handshake(&initiator_msg).unwrap();
+        let initiator_session = initiator.complete_handshake(&responder_msg).unwrap();
+
+        let nonce = [0u8; 12];
+        let data = b"forward 2.0 m/s";
+        let ciphertext = initiator_session.encrypt(data, &nonce).unwrap();
+        let plaintext = responder_session.decrypt(&ciphertext, &nonce).unwrap();
+        assert_eq!(plaintext.as_slice(), data);
+    }
+
+    #[test]
+    fn test_complete_handshake_fails_without_initiate() {
+        let mut initiator = CryptoBox::new("session-2".into());
+        let responder = CryptoBox::new("session-2".into());
+
+        let bogus_msg = responder.initiate_handshake();
+        assert!(initiator.complete_handshake(&bogus_msg).is_err());
+    }
+
+    #[test]
+    fn test_respond_handshake_rejects_tampered_ephemeral_key() {
+        let initiator = CryptoBox::new("session-3".into());
+        let responder = CryptoBox::new("session-3".into());
+
+        let mut bad_msg = CryptoBox::new("scratch".into()).initiate_handshake();
+        bad_msg.verifying_key = initiator.verifying_key;
+
+        assert!(responder.respond_handshake(&bad_msg).is_err());
+    }
+
+    /// Builds two independent `CryptoBox`es over the same cipher key, so
+    /// sequence/nonce/replay-window behavior can be tested without going
+    /// through the handshake's key agreement (covered separately above).
+    /// Given opposite `NonceOrigin`s, same as a real initiator/responder
+    /// pair, so `sender`'s outgoing nonces land in the half of the nonce
+    /// space `receiver.decrypt_received` actually looks in.
+    fn paired_session_boxes() -> (CryptoBox, CryptoBox) {
+        let cipher_key: [u8; 32] = rand::random();
+        let sender = CryptoBox::from_keys(SigningKey::generate(&mut OsRng), &cipher_key, "test-key".into());
+        let receiver = CryptoBox::from_keys_with_origin(
+            SigningKey::generate(&mut OsRng),
+            &cipher_key,
+            "test-key".into(),
+            NonceOrigin::Responder,
+        );
+        (sender, receiver)
+    }
+
+    #[test]
+    fn test_encrypt_next_derives_distinct_nonces_and_roundtrips() {
+        let (mut sender, mut receiver) = paired_session_boxes();
+
+        let (seq0, ct0, rekey0) = sender.encrypt_next(b"one").unwrap();
+        let (seq1, ct1, rekey1) = sender.encrypt_next(b"two").unwrap();
+        assert_eq!((seq0, seq1), (0, 1));
+        assert_ne!(ct0, ct1);
+        assert!(rekey0.is_none() && rekey1.is_none());
+
+        assert_eq!(receiver.decrypt_received(seq0, &ct0).unwrap(), b"one");
+        assert_eq!(receiver.decrypt_received(seq1, &ct1).unwrap(), b"two");
+    }
+
+    /// A handshake's two `CryptoBox`es share the identical symmetric key
+    /// (see `derive_session_cipher`), so a duplex session - both sides
+    /// calling `encrypt_next` - would reuse the same nonce under the same
+    /// key at seq 0 if the roles weren't partitioned. Regression test for
+    /// that AEAD nonce-reuse case: both sides send at the same sequence
+    /// number, and each decrypts only the *other* side's traffic correctly.
+    #[test]
+    fn test_duplex_session_boxes_never_reuse_a_nonce_under_the_shared_key() {
+        let (mut initiator, mut responder) = paired_session_boxes();
+        assert_eq!(initiator.origin, NonceOrigin::Initiator);
+        assert_eq!(responder.origin, NonceOrigin::Responder);
+
+        let (seq_a, ct_a, _) = initiator.encrypt_next(b"initiator-says-hi").unwrap();
+        let (seq_b, ct_b, _) = responder.encrypt_next(b"responder-says-hi").unwrap();
+        // Same sequence number on both sides, but distinct wire ciphertext:
+        // the role marker folded into the nonce keeps them from colliding.
+        assert_eq!(seq_a, seq_b);
+        assert_ne!(ct_a, ct_b);
+
+        assert_eq!(
+            responder.decrypt_received(seq_a, &ct_a).unwrap(),
+            b"initiator-says-hi"
+        );
+        assert_eq!(
+            initiator.decrypt_received(seq_b, &ct_b).unwrap(),
+            b"responder-says-hi"
+        );
+
+        // Each side decrypting its own outgoing ciphertext must fail: that
+        // would mean the nonce spaces collided.
+        assert!(initiator.decrypt_received(seq_a, &ct_a).is_err());
+        assert!(responder.decrypt_received(seq_b, &ct_b).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_received_rejects_exact_replay() {
+        let (mut sender, mut receiver) = paired_session_boxes();
+
+        let (seq, ct, _) = sender.encrypt_next(b"forward").unwrap();
+        assert!(receiver.decrypt_received(seq, &ct).is_ok());
+        assert!(receiver.decrypt_received(seq, &ct).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_received_tolerates_reordering_within_the_window() {
+        let (mut sender, mut receiver) = paired_session_boxes();
+
+        let (seq0, ct0, _) = sender.encrypt_next(b"one").unwrap();
+        let (seq1, ct1, _) = sender.encrypt_next(b"two").unwrap();
+
+        // seq1 arrives before seq0, out of order.
+        assert!(receiver.decrypt_received(seq1, &ct1).is_ok());
+        assert!(receiver.decrypt_received(seq0, &ct0).is_ok());
+    }
+
+    #[test]
+    fn test_decrypt_received_rejects_sequence_below_the_window_floor() {
+        let (mut sender, mut receiver) = paired_session_boxes();
+
+        let (seq0, ct0, _) = sender.encrypt_next(b"one").unwrap();
+        for _ in 0..100 {
+            let (_, ct, _) = sender.encrypt_next(b"filler").unwrap();
+            let seq = sender.seq_counter - 1;
+            receiver.decrypt_received(seq, &ct).unwrap();
+        }
+
+        assert!(receiver.decrypt_received(seq0, &ct0).is_err());
+    }
+
+    #[test]
+    fn test_rekey_status_flags_message_count_threshold() {
+        let mut sender = CryptoBox::new("test-key".into());
+        sender.set_rekey_policy(RekeyPolicy { max_messages: 2, ..RekeyPolicy::default() });
+
+        let (_, _, rekey0) = sender.encrypt_next(b"one").unwrap();
+        let (_, _, rekey1) = sender.encrypt_next(b"two").unwrap();
+
+        assert!(rekey0.is_none());
+        assert_eq!(rekey1, Some(RekeyReason::MessageCount));
+    }
+
+    #[test]
+    fn test_rekey_status_flags_byte_count_threshold() {
+        let mut sender = CryptoBox::new("test-key".into());
+        sender.set_rekey_policy(RekeyPolicy { max_bytes: 4, ..RekeyPolicy::default() });
+
+        let (_, _, rekey) = sender.encrypt_next(b"12345").unwrap();
+        assert_eq!(rekey, Some(RekeyReason::ByteCount));
+    }
 }
@@ -0,0 +1,233 @@
+//! Power-on self-test for the crypto primitives `envelope_crypto` relies on
+//! (AES-256-GCM, Ed25519), run against a small set of embedded
+//! Wycheproof-style known-answer vectors - including tampered-tag and
+//! wrong-key negative cases that must be *rejected*, not just a happy path.
+//! A miscompiled or mis-linked crypto backend is far more dangerous on a
+//! safety-critical link than an obviously broken one, since it can silently
+//! accept forged or undecryptable traffic; `SafetySupervisor` refuses to
+//! operate the robot if this self-test doesn't pass cleanly.
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce as AesNonce};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+struct AesGcmCase {
+    name: &'static str,
+    key_hex: &'static str,
+    nonce_hex: &'static str,
+    aad_hex: &'static str,
+    plaintext_hex: &'static str,
+    ciphertext_and_tag_hex: &'static str,
+    /// `true` if decryption is expected to succeed and recover `plaintext_hex`;
+    /// `false` if it's expected to be rejected (tampered tag, wrong key, ...).
+    valid: bool,
+}
+
+struct Ed25519Case {
+    name: &'static str,
+    verifying_key_hex: &'static str,
+    message_hex: &'static str,
+    signature_hex: &'static str,
+    valid: bool,
+}
+
+fn aes_gcm_cases() -> Vec<AesGcmCase> {
+    vec![
+        AesGcmCase {
+            // NIST/McGrew-Viega all-zero AES-256-GCM test case: zero key,
+            // zero nonce, empty AAD/plaintext.
+            name: "nist-zero-key-zero-nonce-empty-plaintext",
+            key_hex: "0000000000000000000000000000000000000000000000000000000000000000",
+            nonce_hex: "000000000000000000000000",
+            aad_hex: "",
+            plaintext_hex: "",
+            ciphertext_and_tag_hex: "530f8afbc74536b9a963b4f1c4cb738b",
+            valid: true,
+        },
+        AesGcmCase {
+            name: "tampered-tag-is-rejected",
+            key_hex: "0000000000000000000000000000000000000000000000000000000000000000",
+            nonce_hex: "000000000000000000000000",
+            aad_hex: "",
+            plaintext_hex: "",
+            // Last nibble of the genuine tag above flipped (...738b -> ...738c).
+            ciphertext_and_tag_hex: "530f8afbc74536b9a963b4f1c4cb738c",
+            valid: false,
+        },
+        AesGcmCase {
+            name: "wrong-key-is-rejected",
+            // Same nonce/tag as the genuine case, but an all-`11` key instead
+            // of the all-zero one the tag was actually computed under.
+            key_hex: "1111111111111111111111111111111111111111111111111111111111111111",
+            nonce_hex: "000000000000000000000000",
+            aad_hex: "",
+            plaintext_hex: "",
+            ciphertext_and_tag_hex: "530f8afbc74536b9a963b4f1c4cb738b",
+            valid: false,
+        },
+    ]
+}
+
+fn ed25519_cases() -> Vec<Ed25519Case> {
+    vec![
+        Ed25519Case {
+            name: "genuine-signature-over-empty-message",
+            verifying_key_hex: "2152f8d19b791d24453242e15f2eab6cb7cffa7b6a5ed30097960e069881db12",
+            message_hex: "",
+            signature_hex: "3f9f3147d0dd159f334cb800435ae49a2837adae5e6b2394906edc2cfed829785e3dd186eb2fed1319a0451917cb6617fcbe9382e0d1343eb5ffd4a9a2dd820c",
+            valid: true,
+        },
+        Ed25519Case {
+            name: "tampered-signature-is-rejected",
+            verifying_key_hex: "2152f8d19b791d24453242e15f2eab6cb7cffa7b6a5ed30097960e069881db12",
+            message_hex: "",
+            // Last byte of the genuine signature above flipped (...d820c -> ...d820f).
+            signature_hex: "3f9f3147d0dd159f334cb800435ae49a2837adae5e6b2394906edc2cfed829785e3dd186eb2fed1319a0451917cb6617fcbe9382e0d1343eb5ffd4a9a2dd820f",
+            valid: false,
+        },
+        Ed25519Case {
+            // A different, well-formed verifying key that simply isn't the
+            // one the signature above was produced under.
+            name: "wrong-key-is-rejected",
+            verifying_key_hex: "58936604abda112bc94933569c82f8d0cc0ddf92a3f8329f2f448f7f484a594c",
+            message_hex: "",
+            signature_hex: "3f9f3147d0dd159f334cb800435ae49a2837adae5e6b2394906edc2cfed829785e3dd186eb2fed1319a0451917cb6617fcbe9382e0d1343eb5ffd4a9a2dd820c",
+            valid: false,
+        },
+    ]
+}
+
+/// Result of a single known-answer case.
+#[derive(Debug, Clone)]
+pub struct SelfTestResult {
+    pub name: String,
+    pub passed: bool,
+    pub detail: Option<String>,
+}
+
+/// Structured pass/fail report from [`run_crypto_selftest`].
+#[derive(Debug, Clone, Default)]
+pub struct CryptoSelfTestReport {
+    pub results: Vec<SelfTestResult>,
+}
+
+impl CryptoSelfTestReport {
+    pub fn passed(&self) -> bool {
+        !self.results.is_empty() && self.results.iter().all(|r| r.passed)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &SelfTestResult> {
+        self.results.iter().filter(|r| !r.passed)
+    }
+}
+
+fn run_aes_gcm_case(case: &AesGcmCase) -> SelfTestResult {
+    let outcome = (|| -> Result<(), String> {
+        let key_bytes = hex::decode(case.key_hex).map_err(|e| e.to_string())?;
+        let nonce_bytes = hex::decode(case.nonce_hex).map_err(|e| e.to_string())?;
+        let aad = hex::decode(case.aad_hex).map_err(|e| e.to_string())?;
+        let expected_plaintext = hex::decode(case.plaintext_hex).map_err(|e| e.to_string())?;
+        let ciphertext_and_tag = hex::decode(case.ciphertext_and_tag_hex).map_err(|e| e.to_string())?;
+
+        let cipher = Aes256Gcm::new_from_slice(&key_bytes).map_err(|e| e.to_string())?;
+        let nonce = AesNonce::from_slice(&nonce_bytes);
+
+        let result = cipher.decrypt(
+            nonce,
+            Payload { msg: ciphertext_and_tag.as_slice(), aad: aad.as_slice() },
+        );
+
+        match result {
+            Ok(plaintext) if plaintext == expected_plaintext => Ok(()),
+            Ok(_) => Err("decrypted but plaintext did not match the known answer".into()),
+            Err(e) => Err(format!("decryption rejected: {e}")),
+        }
+    })();
+
+    match (case.valid, outcome) {
+        (true, Ok(())) => SelfTestResult { name: case.name.into(), passed: true, detail: None },
+        (false, Err(_)) => SelfTestResult { name: case.name.into(), passed: true, detail: None },
+        (true, Err(detail)) => SelfTestResult { name: case.name.into(), passed: false, detail: Some(detail) },
+        (false, Ok(())) => SelfTestResult {
+            name: case.name.into(),
+            passed: false,
+            detail: Some("expected rejection, but decryption succeeded".into()),
+        },
+    }
+}
+
+fn run_ed25519_case(case: &Ed25519Case) -> SelfTestResult {
+    let outcome = (|| -> Result<(), String> {
+        let key_bytes: [u8; 32] = hex::decode(case.verifying_key_hex)
+            .map_err(|e| e.to_string())?
+            .try_into()
+            .map_err(|_| "verifying key is not 32 bytes".to_string())?;
+        let message = hex::decode(case.message_hex).map_err(|e| e.to_string())?;
+        let signature_bytes = hex::decode(case.signature_hex).map_err(|e| e.to_string())?;
+
+        let verifying_key = VerifyingKey::from_bytes(&key_bytes).map_err(|e| e.to_string())?;
+        let signature = Signature::from_slice(&signature_bytes).map_err(|e| e.to_string())?;
+
+        verifying_key
+            .verify(&message, &signature)
+            .map_err(|e| format!("signature rejected: {e}"))
+    })();
+
+    match (case.valid, outcome) {
+        (true, Ok(())) => SelfTestResult { name: case.name.into(), passed: true, detail: None },
+        (false, Err(_)) => SelfTestResult { name: case.name.into(), passed: true, detail: None },
+        (true, Err(detail)) => SelfTestResult { name: case.name.into(), passed: false, detail: Some(detail) },
+        (false, Ok(())) => SelfTestResult {
+            name: case.name.into(),
+            passed: false,
+            detail: Some("expected rejection, but signature verification succeeded".into()),
+        },
+    }
+}
+
+/// Runs every embedded AES-256-GCM and Ed25519 known-answer case and returns
+/// a structured pass/fail report. A case "passes" when the primitive
+/// produces the expected outcome - the correct plaintext/signature for a
+/// `valid` case, or a rejection for an invalid one (tampered tag, tampered
+/// signature, wrong key).
+pub fn run_crypto_selftest() -> CryptoSelfTestReport {
+    let mut results: Vec<SelfTestResult> = aes_gcm_cases().iter().map(run_aes_gcm_case).collect();
+    results.extend(ed25519_cases().iter().map(run_ed25519_case));
+    CryptoSelfTestReport { results }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selftest_passes_on_a_healthy_build() {
+        let report = run_crypto_selftest();
+        for result in report.failures() {
+            panic!("{}: {:?}", result.name, result.detail);
+        }
+        assert!(report.passed());
+    }
+
+    #[test]
+    fn test_selftest_has_both_primitives_and_negative_cases() {
+        let report = run_crypto_selftest();
+        assert!(report.results.len() >= 6);
+    }
+
+    #[test]
+    fn test_aes_gcm_case_flags_regression_on_an_altered_known_answer() {
+        let mut case = aes_gcm_cases().remove(0);
+        case.valid = false; // the genuine vector should decrypt, not be rejected
+        let result = run_aes_gcm_case(&case);
+        assert!(!result.passed);
+    }
+
+    #[test]
+    fn test_ed25519_case_flags_regression_on_an_altered_known_answer() {
+        let mut case = ed25519_cases().remove(0);
+        case.valid = false; // the genuine vector should verify, not be rejected
+        let result = run_ed25519_case(&case);
+        assert!(!result.passed);
+    }
+}
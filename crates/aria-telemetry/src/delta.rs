@@ -1,6 +1,10 @@
 //! Delta encoding for sequential data compression
 
 use aria_domain::{AriaError, AriaResult, IDeltaCodec};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
 
 pub struct SimpleDeltaCodec {
     previous: Option<Vec<u8>>,
@@ -55,10 +59,302 @@ impl IDeltaCodec for SimpleDeltaCodec {
     }
 }
 
+/// A fixed-width column in a record batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    U32,
+    I64,
+    F32,
+    Bool,
+}
+
+impl ColumnType {
+    fn width(self) -> usize {
+        match self {
+            ColumnType::U32 | ColumnType::F32 => 4,
+            ColumnType::I64 => 8,
+            ColumnType::Bool => 1,
+        }
+    }
+
+    fn is_integer(self) -> bool {
+        matches!(self, ColumnType::U32 | ColumnType::I64)
+    }
+}
+
+const COL_TAG_RAW_RLE: u8 = 0;
+const COL_TAG_DELTA_RLE: u8 = 1;
+const COL_TAG_BIT_RUN: u8 = 2;
+
+/// Columnar delta codec for fixed-layout record batches (poses, timestamps,
+/// battery levels, ...). Each record batch is transposed into one column per
+/// field; every column is then run-length encoded, with monotonic integer
+/// columns additionally delta-encoded against their running value, before the
+/// concatenated column blocks are DEFLATE-compressed. This beats a flat
+/// byte-wise XOR on structured telemetry where most fields repeat or
+/// increment between frames.
+pub struct ColumnarDeltaCodec {
+    schema: Vec<ColumnType>,
+    previous: Option<Vec<u8>>,
+}
+
+impl ColumnarDeltaCodec {
+    pub fn new(schema: Vec<ColumnType>) -> Self {
+        Self {
+            schema,
+            previous: None,
+        }
+    }
+
+    fn row_width(&self) -> usize {
+        self.schema.iter().map(|c| c.width()).sum()
+    }
+
+    /// Reads column `col` of row `row` out of a row-major buffer as a
+    /// zero-extended `u64`, which is a lossless round-trip for every
+    /// supported column width. Checked: returns `AriaError::Compression`
+    /// instead of panicking if `buf` is too short for `row`/`col`.
+    fn read_cell(&self, buf: &[u8], row: usize, col: usize) -> AriaResult<u64> {
+        let row_width = self.row_width();
+        let offset: usize = self.schema[..col].iter().map(|c| c.width()).sum();
+        let start = row * row_width + offset;
+        let width = self.schema[col].width();
+        let cell = buf
+            .get(start..start + width)
+            .ok_or_else(|| AriaError::Compression("decoded row/column out of bounds".into()))?;
+        let mut bytes = [0u8; 8];
+        bytes[..width].copy_from_slice(cell);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// Mirror of `read_cell` for the write side: returns
+    /// `AriaError::Compression` instead of panicking if `buf` is too short
+    /// for `row`/`col`, so a decoder bug upstream (e.g. a column whose
+    /// decoded row count disagrees with the others) surfaces as an error
+    /// rather than an out-of-bounds panic.
+    fn write_cell(&self, buf: &mut [u8], row: usize, col: usize, value: u64) -> AriaResult<()> {
+        let row_width = self.row_width();
+        let offset: usize = self.schema[..col].iter().map(|c| c.width()).sum();
+        let start = row * row_width + offset;
+        let width = self.schema[col].width();
+        buf.get_mut(start..start + width)
+            .ok_or_else(|| AriaError::Compression("decoded row/column out of bounds".into()))?
+            .copy_from_slice(&value.to_le_bytes()[..width]);
+        Ok(())
+    }
+
+    /// Last row's per-column values of `buf`, used as the delta baseline
+    /// carried over from the previous batch.
+    fn last_row_values(&self, buf: &[u8]) -> Option<Vec<u64>> {
+        let row_width = self.row_width();
+        if row_width == 0 || buf.is_empty() || buf.len() % row_width != 0 {
+            return None;
+        }
+        let last_row = buf.len() / row_width - 1;
+        (0..self.schema.len())
+            .map(|col| self.read_cell(buf, last_row, col).ok())
+            .collect()
+    }
+
+    fn encode_column(&self, ty: ColumnType, values: &[u64], seed: Option<u64>) -> Vec<u8> {
+        let mut block = Vec::new();
+
+        if ty == ColumnType::Bool {
+            block.push(COL_TAG_BIT_RUN);
+            block.extend_from_slice(&(values.len() as u32).to_le_bytes());
+            block.extend_from_slice(&rle_encode(values));
+            return block;
+        }
+
+        if ty.is_integer() {
+            let monotonic = seed
+                .into_iter()
+                .chain(values.iter().copied())
+                .map(|v| v as i64)
+                .collect::<Vec<_>>()
+                .windows(2)
+                .all(|w| w[1] >= w[0]);
+
+            if monotonic {
+                let mut prev = seed.map(|s| s as i64).unwrap_or(0);
+                let diffs: Vec<u64> = values
+                    .iter()
+                    .map(|&v| {
+                        let v = v as i64;
+                        let d = v.wrapping_sub(prev);
+                        prev = v;
+                        d as u64
+                    })
+                    .collect();
+                block.push(COL_TAG_DELTA_RLE);
+                block.extend_from_slice(&(values.len() as u32).to_le_bytes());
+                block.extend_from_slice(&rle_encode(&diffs));
+                return block;
+            }
+        }
+
+        block.push(COL_TAG_RAW_RLE);
+        block.extend_from_slice(&(values.len() as u32).to_le_bytes());
+        block.extend_from_slice(&rle_encode(values));
+        block
+    }
+
+    fn decode_column(
+        &self,
+        buf: &[u8],
+        pos: &mut usize,
+        seed: Option<u64>,
+    ) -> AriaResult<Vec<u64>> {
+        let tag = *buf
+            .get(*pos)
+            .ok_or_else(|| AriaError::Compression("truncated column block".into()))?;
+        *pos += 1;
+        let num_rows = u32::from_le_bytes(
+            buf.get(*pos..*pos + 4)
+                .and_then(|b| b.try_into().ok())
+                .ok_or_else(|| AriaError::Compression("truncated column header".into()))?,
+        ) as usize;
+        *pos += 4;
+
+        let (raw, consumed) = rle_decode(&buf[*pos..])?;
+        *pos += consumed;
+        if raw.len() != num_rows {
+            return Err(AriaError::Compression("column row count mismatch".into()));
+        }
+
+        let values = match tag {
+            COL_TAG_RAW_RLE | COL_TAG_BIT_RUN => raw,
+            COL_TAG_DELTA_RLE => {
+                let mut prev = seed.map(|s| s as i64).unwrap_or(0);
+                raw.iter()
+                    .map(|&d| {
+                        let v = prev.wrapping_add(d as i64);
+                        prev = v;
+                        v as u64
+                    })
+                    .collect()
+            }
+            other => return Err(AriaError::Compression(format!("unknown column tag {other}"))),
+        };
+        Ok(values)
+    }
+}
+
+impl IDeltaCodec for ColumnarDeltaCodec {
+    fn encode(&mut self, current: &[u8], previous: Option<&[u8]>) -> AriaResult<Vec<u8>> {
+        let row_width = self.row_width();
+        if row_width == 0 || current.len() % row_width != 0 {
+            return Err(AriaError::Compression(
+                "record batch is not a multiple of the schema's row width".into(),
+            ));
+        }
+        let num_rows = current.len() / row_width;
+        let seed_row = previous.or(self.previous.as_deref()).and_then(|p| self.last_row_values(p));
+
+        let mut raw = Vec::new();
+        for (col, &ty) in self.schema.iter().enumerate() {
+            let values: Vec<u64> = (0..num_rows)
+                .map(|row| self.read_cell(current, row, col))
+                .collect::<AriaResult<Vec<u64>>>()?;
+            let seed = seed_row.as_ref().map(|s| s[col]);
+            raw.extend_from_slice(&self.encode_column(ty, &values, seed));
+        }
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&raw)
+            .map_err(|e| AriaError::Compression(e.to_string()))?;
+        let compressed = encoder
+            .finish()
+            .map_err(|e| AriaError::Compression(e.to_string()))?;
+
+        self.previous = Some(current.to_vec());
+        Ok(compressed)
+    }
+
+    fn decode(&mut self, delta: &[u8], previous: Option<&[u8]>) -> AriaResult<Vec<u8>> {
+        let seed_row = previous.or(self.previous.as_deref()).and_then(|p| self.last_row_values(p));
+
+        let mut raw = Vec::new();
+        DeflateDecoder::new(delta)
+            .read_to_end(&mut raw)
+            .map_err(|e| AriaError::Compression(e.to_string()))?;
+
+        let mut pos = 0;
+        let mut columns = Vec::with_capacity(self.schema.len());
+        for col in 0..self.schema.len() {
+            let seed = seed_row.as_ref().map(|s| s[col]);
+            columns.push(self.decode_column(&raw, &mut pos, seed)?);
+        }
+
+        let num_rows = columns.first().map(|c| c.len()).unwrap_or(0);
+        if columns.iter().any(|c| c.len() != num_rows) {
+            return Err(AriaError::Compression(
+                "columns decoded to differing row counts".into(),
+            ));
+        }
+        let row_width = self.row_width();
+        let mut out = vec![0u8; num_rows * row_width];
+        for (col, values) in columns.iter().enumerate() {
+            for (row, &value) in values.iter().enumerate() {
+                self.write_cell(&mut out, row, col, value)?;
+            }
+        }
+
+        self.previous = Some(out.clone());
+        Ok(out)
+    }
+}
+
+fn rle_encode(values: &[u64]) -> Vec<u8> {
+    let mut runs: Vec<(u32, u64)> = Vec::new();
+    for &v in values {
+        match runs.last_mut() {
+            Some((count, value)) if *value == v => *count += 1,
+            _ => runs.push((1, v)),
+        }
+    }
+
+    let mut out = Vec::with_capacity(4 + runs.len() * 12);
+    out.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+    for (count, value) in runs {
+        out.extend_from_slice(&count.to_le_bytes());
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    out
+}
+
+/// Returns the decoded run values plus the number of bytes consumed from `buf`.
+fn rle_decode(buf: &[u8]) -> AriaResult<(Vec<u64>, usize)> {
+    let num_runs = u32::from_le_bytes(
+        buf.get(0..4)
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| AriaError::Compression("truncated RLE header".into()))?,
+    ) as usize;
+    let mut pos = 4;
+    let mut values = Vec::new();
+    for _ in 0..num_runs {
+        let count = u32::from_le_bytes(
+            buf.get(pos..pos + 4)
+                .and_then(|b| b.try_into().ok())
+                .ok_or_else(|| AriaError::Compression("truncated RLE run".into()))?,
+        );
+        pos += 4;
+        let value = u64::from_le_bytes(
+            buf.get(pos..pos + 8)
+                .and_then(|b| b.try_into().ok())
+                .ok_or_else(|| AriaError::Compression("truncated RLE run".into()))?,
+        );
+        pos += 8;
+        values.extend(std::iter::repeat(value).take(count as usize));
+    }
+    Ok((values, pos))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+
     #[test]
     fn test_delta_roundtrip() {
         let mut encoder = SimpleDeltaCodec::new();
@@ -91,4 +387,97 @@ mod tests {
         assert_eq!(delta1, frame1); // First is full
         assert!(delta2.iter().take(4).all(|&b| b == 0)); // First 4 bytes are same
     }
+
+    fn row(seq: u32, battery: f32, armed: bool) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&seq.to_le_bytes());
+        bytes.extend_from_slice(&battery.to_le_bytes());
+        bytes.push(armed as u8);
+        bytes
+    }
+
+    fn schema() -> Vec<ColumnType> {
+        vec![ColumnType::U32, ColumnType::F32, ColumnType::Bool]
+    }
+
+    #[test]
+    fn test_columnar_roundtrip_single_batch() {
+        let mut encoder = ColumnarDeltaCodec::new(schema());
+        let mut decoder = ColumnarDeltaCodec::new(schema());
+
+        let mut batch = Vec::new();
+        batch.extend(row(1, 98.0, true));
+        batch.extend(row(2, 98.0, true));
+        batch.extend(row(3, 97.5, false));
+
+        let encoded = encoder.encode(&batch, None).unwrap();
+        let decoded = decoder.decode(&encoded, None).unwrap();
+        assert_eq!(decoded, batch);
+    }
+
+    #[test]
+    fn test_columnar_roundtrip_across_batches() {
+        let mut encoder = ColumnarDeltaCodec::new(schema());
+        let mut decoder = ColumnarDeltaCodec::new(schema());
+
+        let batch1 = [row(10, 100.0, false), row(11, 100.0, false)].concat();
+        let batch2 = [row(12, 100.0, false), row(13, 99.0, true)].concat();
+
+        let encoded1 = encoder.encode(&batch1, None).unwrap();
+        let decoded1 = decoder.decode(&encoded1, None).unwrap();
+        assert_eq!(decoded1, batch1);
+
+        let encoded2 = encoder.encode(&batch2, None).unwrap();
+        let decoded2 = decoder.decode(&encoded2, None).unwrap();
+        assert_eq!(decoded2, batch2);
+    }
+
+    #[test]
+    fn test_columnar_compresses_constant_stream() {
+        let mut codec = ColumnarDeltaCodec::new(schema());
+
+        let mut batch = Vec::new();
+        for i in 0..64u32 {
+            batch.extend(row(i, 100.0, true));
+        }
+
+        let encoded = codec.encode(&batch, None).unwrap();
+        assert!(encoded.len() < batch.len());
+    }
+
+    #[test]
+    fn test_columnar_decode_rejects_truncated_block_instead_of_panicking() {
+        let mut encoder = ColumnarDeltaCodec::new(schema());
+        let mut decoder = ColumnarDeltaCodec::new(schema());
+
+        let mut batch = Vec::new();
+        batch.extend(row(1, 98.0, true));
+        let encoded = encoder.encode(&batch, None).unwrap();
+
+        for cut in 1..encoded.len() {
+            assert!(decoder.decode(&encoded[..cut], None).is_err());
+        }
+    }
+
+    #[test]
+    fn test_columnar_decode_rejects_mismatched_column_row_counts_instead_of_panicking() {
+        // Hand-build a 2-column [U32, U32] block where column 0 decodes to
+        // 1 row and column 1 decodes to 3 rows - same corruption shape as a
+        // truncated block, but each column's own header is internally
+        // consistent, so only a cross-column length check catches it.
+        let mut block = Vec::new();
+        block.push(COL_TAG_RAW_RLE);
+        block.extend_from_slice(&1u32.to_le_bytes());
+        block.extend_from_slice(&rle_encode(&[7]));
+        block.push(COL_TAG_RAW_RLE);
+        block.extend_from_slice(&3u32.to_le_bytes());
+        block.extend_from_slice(&rle_encode(&[1, 2, 3]));
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&block).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut decoder = ColumnarDeltaCodec::new(vec![ColumnType::U32, ColumnType::U32]);
+        assert!(decoder.decode(&compressed, None).is_err());
+    }
 }
@@ -0,0 +1,269 @@
+//! Hybrid envelope encryption: per-envelope AES-256-GCM payload encryption,
+//! X25519 ECDH + HKDF-SHA256 content-key wrapping, and Ed25519 signing over
+//! the ciphertext plus the authenticated header fields.
+//!
+//! `seal` is the TX-side operation (generate content key, encrypt, wrap,
+//! sign); `open` is the RX-side operation (verify, unwrap, decrypt), and
+//! fails closed on any auth-tag or signature mismatch.
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce as AesNonce};
+use aria_domain::{AriaError, AriaResult, CryptoInfo, Envelope};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use hkdf::Hkdf;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::Sha256;
+use x25519_dalek::{EphemeralSecret, PublicKey as X25519PublicKey, StaticSecret};
+
+const CONTENT_KEY_LEN: usize = 32;
+const GCM_NONCE_LEN: usize = 12;
+/// Domain-separation label for the HKDF expand step, so the derived
+/// key-encryption key can't be confused with any other use of the same ECDH
+/// shared secret.
+const HKDF_INFO: &[u8] = b"aria-envelope-key-wrap-v1";
+
+/// A node's Ed25519 signing identity: the private key used to sign sealed
+/// envelopes, and the public key peers use to verify them.
+pub struct SigningIdentity {
+    signing_key: SigningKey,
+    verifying_key: VerifyingKey,
+}
+
+impl SigningIdentity {
+    pub fn generate() -> Self {
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let verifying_key = signing_key.verifying_key();
+        Self { signing_key, verifying_key }
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.verifying_key
+    }
+}
+
+/// A recipient's long-lived X25519 key-agreement keypair, identified by the
+/// `key_id` that will appear in `CryptoInfo::key_id` for anything wrapped to
+/// its public key.
+pub struct RecipientKeyPair {
+    key_id: String,
+    secret: StaticSecret,
+    public: X25519PublicKey,
+}
+
+impl RecipientKeyPair {
+    pub fn generate(key_id: String) -> Self {
+        let secret = StaticSecret::random_from_rng(OsRng);
+        let public = X25519PublicKey::from(&secret);
+        Self { key_id, secret, public }
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub fn public_key(&self) -> X25519PublicKey {
+        self.public
+    }
+}
+
+/// Header fields authenticated as GCM associated data and covered by the
+/// Ed25519 signature — everything about the envelope a tampered copy could
+/// otherwise lie about without invalidating the payload ciphertext.
+fn associated_data(envelope: &Envelope) -> Vec<u8> {
+    let mut ad = Vec::new();
+    ad.extend_from_slice(envelope.topic.as_bytes());
+    ad.extend_from_slice(&envelope.schema_id.to_le_bytes());
+    ad.extend_from_slice(&envelope.metadata.sequence_number.to_le_bytes());
+    ad
+}
+
+fn derive_kek(shared_secret: &x25519_dalek::SharedSecret) -> AriaResult<Aes256Gcm> {
+    let hk = Hkdf::<Sha256>::new(None, shared_secret.as_bytes());
+    let mut kek_bytes = [0u8; CONTENT_KEY_LEN];
+    hk.expand(HKDF_INFO, &mut kek_bytes)
+        .map_err(|e| AriaError::Crypto(format!("HKDF expand failed: {e}")))?;
+    Ok(Aes256Gcm::new(&kek_bytes.into()))
+}
+
+/// Encrypts `envelope.payload` with a fresh per-envelope AES-256-GCM content
+/// key, wraps that key to `recipient` via ephemeral X25519 ECDH + HKDF-SHA256,
+/// and signs the ciphertext + header with `identity`. Populates
+/// `metadata.crypto_info` with everything the recipient needs to reverse it.
+pub fn seal(envelope: &mut Envelope, recipient: &RecipientKeyPair, identity: &SigningIdentity) -> AriaResult<()> {
+    let mut content_key_bytes = [0u8; CONTENT_KEY_LEN];
+    OsRng.fill_bytes(&mut content_key_bytes);
+    let content_cipher = Aes256Gcm::new(&content_key_bytes.into());
+
+    let mut nonce_bytes = [0u8; GCM_NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce_bytes);
+    let nonce = AesNonce::from_slice(&nonce_bytes);
+
+    let ad = associated_data(envelope);
+    let ciphertext = content_cipher
+        .encrypt(nonce, Payload { msg: envelope.payload.as_slice(), aad: ad.as_slice() })
+        .map_err(|e| AriaError::Crypto(format!("payload encryption failed: {e}")))?;
+
+    // Ephemeral ECDH: a fresh keypair per envelope means the derived KEK is
+    // single-use, so the key-wrap step below can safely use a fixed nonce.
+    let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+    let ephemeral_public = X25519PublicKey::from(&ephemeral_secret);
+    let shared_secret = ephemeral_secret.diffie_hellman(&recipient.public);
+    let kek_cipher = derive_kek(&shared_secret)?;
+
+    let wrap_nonce = AesNonce::from_slice(&[0u8; GCM_NONCE_LEN]);
+    let wrapped_key = kek_cipher
+        .encrypt(wrap_nonce, content_key_bytes.as_slice())
+        .map_err(|e| AriaError::Crypto(format!("content key wrap failed: {e}")))?;
+
+    let mut signed_over = ciphertext.clone();
+    signed_over.extend_from_slice(&ad);
+    let signature = identity.signing_key.sign(&signed_over);
+
+    envelope.payload = ciphertext;
+    envelope.metadata.crypto_info = Some(CryptoInfo {
+        signature: signature.to_bytes().to_vec(),
+        key_id: recipient.key_id.clone(),
+        nonce: nonce_bytes.to_vec(),
+        ephemeral_public_key: ephemeral_public.as_bytes().to_vec(),
+        wrapped_key,
+    });
+
+    Ok(())
+}
+
+/// Verifies the Ed25519 signature against `sender`, unwraps the content key
+/// via `recipient`'s X25519 secret, and decrypts `envelope.payload` in
+/// place. Fails closed: any signature, key-unwrap, or auth-tag mismatch
+/// returns `Err` and leaves the envelope untouched.
+pub fn open(envelope: &mut Envelope, recipient: &RecipientKeyPair, sender: &VerifyingKey) -> AriaResult<()> {
+    let crypto_info = envelope
+        .metadata
+        .crypto_info
+        .as_ref()
+        .ok_or_else(|| AriaError::Crypto("envelope has no crypto_info".into()))?
+        .clone();
+
+    let ad = associated_data(envelope);
+    let mut signed_over = envelope.payload.clone();
+    signed_over.extend_from_slice(&ad);
+    let signature = Signature::from_slice(&crypto_info.signature)
+        .map_err(|e| AriaError::Crypto(format!("malformed signature: {e}")))?;
+    sender
+        .verify(&signed_over, &signature)
+        .map_err(|_| AriaError::Crypto("signature verification failed".into()))?;
+
+    let ephemeral_public_bytes: [u8; 32] = crypto_info
+        .ephemeral_public_key
+        .as_slice()
+        .try_into()
+        .map_err(|_| AriaError::Crypto("malformed ephemeral public key".into()))?;
+    let ephemeral_public = X25519PublicKey::from(ephemeral_public_bytes);
+    let shared_secret = recipient.secret.diffie_hellman(&ephemeral_public);
+    let kek_cipher = derive_kek(&shared_secret)?;
+
+    let wrap_nonce = AesNonce::from_slice(&[0u8; GCM_NONCE_LEN]);
+    let content_key_bytes = kek_cipher
+        .decrypt(wrap_nonce, crypto_info.wrapped_key.as_slice())
+        .map_err(|e| AriaError::Crypto(format!("content key unwrap failed: {e}")))?;
+
+    let content_cipher = Aes256Gcm::new_from_slice(&content_key_bytes)
+        .map_err(|e| AriaError::Crypto(format!("malformed content key: {e}")))?;
+    let nonce = AesNonce::from_slice(&crypto_info.nonce);
+    let plaintext = content_cipher
+        .decrypt(nonce, Payload { msg: envelope.payload.as_slice(), aad: ad.as_slice() })
+        .map_err(|e| AriaError::Crypto(format!("payload decryption failed: {e}")))?;
+
+    envelope.payload = plaintext;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aria_domain::{CodecKind, EnvelopeMetadata, Priority};
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn make_envelope(payload: &[u8]) -> Envelope {
+        Envelope {
+            id: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            schema_id: 7,
+            priority: Priority::P1,
+            topic: "cmd.move".into(),
+            payload: payload.to_vec(),
+            metadata: EnvelopeMetadata {
+                source_node: "aria-send".into(),
+                sequence_number: 42,
+                group_id: None,
+                fragment_info: None,
+                fec_info: None,
+                crypto_info: None,
+                qos_class: "default".into(),
+                codec: CodecKind::Protobuf,
+            },
+        }
+    }
+
+    #[test]
+    fn test_seal_then_open_roundtrips_payload() {
+        let recipient = RecipientKeyPair::generate("recv-1".into());
+        let identity = SigningIdentity::generate();
+        let mut envelope = make_envelope(b"forward 2.0 m/s");
+
+        seal(&mut envelope, &recipient, &identity).unwrap();
+        assert_ne!(envelope.payload, b"forward 2.0 m/s");
+        let crypto_info = envelope.metadata.crypto_info.as_ref().unwrap();
+        assert_eq!(crypto_info.key_id, "recv-1");
+
+        open(&mut envelope, &recipient, &identity.verifying_key()).unwrap();
+        assert_eq!(envelope.payload, b"forward 2.0 m/s");
+    }
+
+    #[test]
+    fn test_open_fails_closed_on_tampered_ciphertext() {
+        let recipient = RecipientKeyPair::generate("recv-1".into());
+        let identity = SigningIdentity::generate();
+        let mut envelope = make_envelope(b"forward 2.0 m/s");
+        seal(&mut envelope, &recipient, &identity).unwrap();
+
+        envelope.payload[0] ^= 0xFF;
+
+        assert!(open(&mut envelope, &recipient, &identity.verifying_key()).is_err());
+    }
+
+    #[test]
+    fn test_open_fails_closed_on_wrong_signer() {
+        let recipient = RecipientKeyPair::generate("recv-1".into());
+        let identity = SigningIdentity::generate();
+        let impostor = SigningIdentity::generate();
+        let mut envelope = make_envelope(b"forward 2.0 m/s");
+        seal(&mut envelope, &recipient, &identity).unwrap();
+
+        assert!(open(&mut envelope, &recipient, &impostor.verifying_key()).is_err());
+    }
+
+    #[test]
+    fn test_open_fails_closed_for_wrong_recipient() {
+        let recipient = RecipientKeyPair::generate("recv-1".into());
+        let other = RecipientKeyPair::generate("recv-2".into());
+        let identity = SigningIdentity::generate();
+        let mut envelope = make_envelope(b"forward 2.0 m/s");
+        seal(&mut envelope, &recipient, &identity).unwrap();
+
+        assert!(open(&mut envelope, &other, &identity.verifying_key()).is_err());
+    }
+
+    #[test]
+    fn test_seal_authenticates_header_against_tampering() {
+        let recipient = RecipientKeyPair::generate("recv-1".into());
+        let identity = SigningIdentity::generate();
+        let mut envelope = make_envelope(b"forward 2.0 m/s");
+        seal(&mut envelope, &recipient, &identity).unwrap();
+
+        envelope.metadata.sequence_number += 1;
+
+        assert!(open(&mut envelope, &recipient, &identity.verifying_key()).is_err());
+    }
+}
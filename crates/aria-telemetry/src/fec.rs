@@ -10,8 +10,10 @@ impl IFEC for ReedSolomonFec {
         let rs = ReedSolomon::new(k, m)
             .map_err(|e| AriaError::Fec(format!("Failed to create RS encoder: {:?}", e)))?;
         
-        // Calculate shard size
-        let shard_size = (data.len() + k - 1) / k;
+        // Calculate shard size. Clamp to at least 1: `chunks()` below panics
+        // on a zero-size chunk, and an empty payload would otherwise compute
+        // shard_size == 0.
+        let shard_size = std::cmp::max(1, (data.len() + k - 1) / k);
         let padded_size = shard_size * k;
         
         // Pad data
@@ -61,10 +63,104 @@ impl IFEC for ReedSolomonFec {
     }
 }
 
+/// Ceiling on FEC overhead as a fraction of `k`, e.g. `0.5` caps `m` at `k/2`
+/// so redundancy can't balloon without bound on a very lossy link.
+const DEFAULT_MAX_OVERHEAD_RATIO: f32 = 0.5;
+/// Target probability that a block is still undecodable after FEC.
+const DEFAULT_TARGET_RESIDUAL: f64 = 1e-4;
+
+/// Wraps `ReedSolomonFec` with a parity count that adapts to the observed
+/// per-fragment loss probability instead of a fixed redundancy ratio. Given
+/// `k` data shards and a loss estimate `p` (e.g. from
+/// `RecoveryManager::estimated_loss_rate`), `recompute` picks the smallest
+/// `m` whose residual block-loss probability drops below `target_residual`,
+/// capped by `max_overhead_ratio`.
+pub struct AdaptiveFec {
+    fec: ReedSolomonFec,
+    k: usize,
+    target_residual: f64,
+    max_overhead_ratio: f32,
+    current_m: usize,
+}
+
+impl AdaptiveFec {
+    pub fn new(k: usize, target_residual: f64, max_overhead_ratio: f32) -> Self {
+        Self {
+            fec: ReedSolomonFec,
+            k,
+            target_residual,
+            max_overhead_ratio,
+            current_m: 1,
+        }
+    }
+
+    /// Convenience constructor using the repo's default residual target and
+    /// overhead cap.
+    pub fn with_defaults(k: usize) -> Self {
+        Self::new(k, DEFAULT_TARGET_RESIDUAL, DEFAULT_MAX_OVERHEAD_RATIO)
+    }
+
+    fn max_m(&self) -> usize {
+        ((self.k as f32 * self.max_overhead_ratio).ceil() as usize).max(1)
+    }
+
+    /// Re-derive the parity count for a freshly observed loss probability
+    /// `p`, returning the `(k, m)` pair now in effect.
+    pub fn recompute(&mut self, p: f64) -> (usize, usize) {
+        let max_m = self.max_m();
+        let mut m = 1;
+        while m < max_m && residual_loss_probability(self.k, m, p) > self.target_residual {
+            m += 1;
+        }
+        self.current_m = m;
+        (self.k, self.current_m)
+    }
+
+    /// The `(k, m)` pair currently in effect, without recomputing it.
+    pub fn current_params(&self) -> (usize, usize) {
+        (self.k, self.current_m)
+    }
+
+    /// Re-derive `m` for the given loss estimate, then encode `data` with it.
+    /// Returns the shards alongside the `(k, m)` actually used, so the
+    /// caller can record it in the emitted `FecInfo`.
+    pub fn encode(&mut self, data: &[u8], p: f64) -> AriaResult<(Vec<Vec<u8>>, usize, usize)> {
+        let (k, m) = self.recompute(p);
+        let shards = self.fec.encode(data, k, m)?;
+        Ok((shards, k, m))
+    }
+}
+
+/// Probability that more than `m` of the `k + m` shards are lost under
+/// independent per-shard loss probability `p`, i.e. that the block is no
+/// longer reconstructible (the binomial tail beyond the parity budget).
+fn residual_loss_probability(k: usize, m: usize, p: f64) -> f64 {
+    let n = k + m;
+    if p <= 0.0 {
+        return 0.0;
+    }
+    if p >= 1.0 {
+        return 1.0;
+    }
+    ((m + 1)..=n)
+        .map(|i| binomial_coefficient(n, i) * p.powi(i as i32) * (1.0 - p).powi((n - i) as i32))
+        .sum()
+}
+
+/// `n choose k`, computed iteratively as `f64` to avoid factorial overflow.
+fn binomial_coefficient(n: usize, k: usize) -> f64 {
+    let k = k.min(n - k);
+    let mut result = 1.0;
+    for i in 0..k {
+        result *= (n - i) as f64 / (i + 1) as f64;
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+
     #[test]
     fn test_fec_no_loss() {
         let fec = ReedSolomonFec;
@@ -118,4 +214,53 @@ mod tests {
         let result = fec.decode(&fragments, k, m);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_fec_encode_empty_payload_does_not_panic() {
+        let fec = ReedSolomonFec;
+        let shards = fec.encode(&[], 1, 2).unwrap();
+        assert_eq!(shards.len(), 3);
+        assert!(shards.iter().all(|s| s.len() == 1));
+    }
+
+    #[test]
+    fn test_residual_probability_decreases_with_m() {
+        let p = 0.1;
+        let worse = residual_loss_probability(8, 1, p);
+        let better = residual_loss_probability(8, 4, p);
+        assert!(better < worse);
+    }
+
+    #[test]
+    fn test_adaptive_fec_increases_parity_with_loss() {
+        let mut adaptive = AdaptiveFec::with_defaults(8);
+
+        let (_, m_quiet) = adaptive.recompute(0.001);
+        let (_, m_lossy) = adaptive.recompute(0.2);
+
+        assert!(m_lossy > m_quiet);
+        assert_eq!(adaptive.current_params().1, m_lossy);
+    }
+
+    #[test]
+    fn test_adaptive_fec_respects_overhead_cap() {
+        let mut adaptive = AdaptiveFec::new(8, 1e-9, 0.25);
+        let (k, m) = adaptive.recompute(0.9);
+        assert_eq!(k, 8);
+        assert!(m <= 2); // ceil(8 * 0.25)
+    }
+
+    #[test]
+    fn test_adaptive_fec_encode_produces_k_plus_m_shards() {
+        let mut adaptive = AdaptiveFec::with_defaults(4);
+        let original = b"adaptive fec payload";
+
+        let (shards, k, m) = adaptive.encode(original, 0.1).unwrap();
+        assert_eq!(shards.len(), k + m);
+
+        let fec = ReedSolomonFec;
+        let fragments: Vec<Option<Vec<u8>>> = shards.into_iter().map(Some).collect();
+        let decoded = fec.decode(&fragments, k, m).unwrap();
+        assert_eq!(&decoded[..original.len()], original);
+    }
 }
@@ -8,6 +8,7 @@
 //! - Packetization (fragmentation/defragmentation)
 //! - Security (sign-then-encrypt)
 //! - QoS (priority queues with token bucket)
+//! - Obfuscation (pluggable, DPI-resistant wire framing)
 //! - Transports (QUIC, MQTT-SN, DTN)
 //! - Recovery (loss concealment + integrity checks)
 
@@ -18,7 +19,11 @@ pub mod ccem;
 pub mod fec;
 pub mod packetization;
 pub mod crypto;
+pub mod envelope_crypto;
+pub mod crypto_selftest;
+pub mod threshold;
 pub mod qos;
+pub mod obfs;
 pub mod transport;
 pub mod recovery;
 pub mod router;
@@ -31,7 +36,11 @@ pub use ccem::*;
 pub use fec::*;
 pub use packetization::*;
 pub use crypto::*;
+pub use envelope_crypto::*;
+pub use crypto_selftest::*;
+pub use threshold::*;
 pub use qos::*;
+pub use obfs::*;
 pub use transport::*;
 pub use recovery::*;
 pub use router::*;
@@ -15,6 +15,8 @@ impl LinkHealthController {
                 cpu_usage: 0.0,
                 memory_mb: 0.0,
                 bandwidth_mbps: 0.0,
+                jitter_ms: 0.0,
+                playout_delay_ms: 0.0,
             },
         }
     }
@@ -50,12 +52,14 @@ mod tests {
     #[test]
     fn test_link_health_advises_fec() {
         let mut controller = LinkHealthController::new();
-        let mut metrics = SystemMetrics {
+        let metrics = SystemMetrics {
             packet_loss_rate: 0.15,
             latency_ms: 50.0,
             cpu_usage: 30.0,
             memory_mb: 512.0,
             bandwidth_mbps: 5.0,
+            jitter_ms: 0.0,
+            playout_delay_ms: 0.0,
         };
         
         controller.update_metrics(metrics);
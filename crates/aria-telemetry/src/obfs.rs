@@ -0,0 +1,294 @@
+//! Traffic obfuscation: a pluggable wire-framing stage between `qos` and
+//! `transport`, modeled on obfs4/o5-style pluggable transports. Even though
+//! `crypto`/`envelope_crypto` already makes payloads opaque, an adversary on
+//! a contested link can still fingerprint ARIA traffic from packet sizes
+//! and inter-packet timing alone. This module frames each already-sealed
+//! envelope with a length-prefixed, randomly-padded wrapper so on-wire
+//! sizes are decoupled from payload sizes, and can emit chaff frames to
+//! fill timing gaps when a priority queue is idle. It adds no
+//! confidentiality of its own - it runs after the AEAD step, not instead of
+//! it - and is opt-in per topic via [`NullObfuscator`].
+
+use aria_domain::{AriaError, AriaResult, IObfuscator, QoSPolicy};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use std::time::Instant;
+
+/// 4-byte big-endian total wire length, 4-byte big-endian real payload
+/// length, then `real_len` payload bytes, then `total_len - HEADER_LEN -
+/// real_len` random padding bytes.
+const HEADER_LEN: usize = 8;
+/// Upper bound on padding added to a single frame, so one envelope can't
+/// balloon itself into a denial-of-service on the link.
+const MAX_PADDING_BYTES: usize = 256;
+
+/// Opt-in per-topic default: passes frames through unchanged. Topics that
+/// don't need DPI resistance pay no framing or padding overhead.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullObfuscator;
+
+impl IObfuscator for NullObfuscator {
+    fn obfuscate(&mut self, frame: &[u8]) -> Vec<u8> {
+        frame.to_vec()
+    }
+
+    fn deobfuscate(&mut self, wire: &[u8]) -> AriaResult<Vec<Vec<u8>>> {
+        Ok(vec![wire.to_vec()])
+    }
+}
+
+/// Token bucket sized from a topic's own `QoSPolicy`, so padding bytes and
+/// chaff frames can never themselves exceed the rate limit already set for
+/// that topic's real traffic. Mirrors `qos::TokenBucket`.
+struct ObfsBudget {
+    capacity: f32,
+    tokens: f32,
+    refill_rate: f32,
+    last_refill: Instant,
+}
+
+impl ObfsBudget {
+    fn from_policy(policy: &QoSPolicy) -> Self {
+        Self {
+            capacity: policy.burst_size as f32,
+            tokens: policy.burst_size as f32,
+            refill_rate: policy.max_rate_per_sec,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f32();
+        self.tokens = (self.tokens + elapsed * self.refill_rate).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    fn try_consume(&mut self, count: f32) -> bool {
+        self.refill();
+        if self.tokens >= count {
+            self.tokens -= count;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+fn random_padding_len() -> usize {
+    (OsRng.next_u32() as usize) % (MAX_PADDING_BYTES + 1)
+}
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+/// obfs4/o5-style obfuscator: length-prefixed, randomly-padded wire framing
+/// plus budgeted chaff-frame generation, so packet sizes and idle-queue
+/// gaps stop leaking information to a DPI observer.
+pub struct PaddedObfuscator {
+    budget: ObfsBudget,
+    recv_buffer: Vec<u8>,
+}
+
+impl PaddedObfuscator {
+    /// `policy` should be the same `QoSPolicy` already governing this
+    /// topic, so padding/chaff never outruns the topic's own rate limit.
+    pub fn new(policy: &QoSPolicy) -> Self {
+        Self {
+            budget: ObfsBudget::from_policy(policy),
+            recv_buffer: Vec::new(),
+        }
+    }
+
+    fn frame(&self, payload: &[u8], padding_len: usize) -> Vec<u8> {
+        let total_len = HEADER_LEN + payload.len() + padding_len;
+        let mut wire = Vec::with_capacity(total_len);
+        wire.extend_from_slice(&(total_len as u32).to_be_bytes());
+        wire.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        wire.extend_from_slice(payload);
+        wire.extend_from_slice(&random_bytes(padding_len));
+        wire
+    }
+
+    /// Called by the driving priority queue when it has nothing real to
+    /// send. Emits a chaff frame - on the wire, indistinguishable from a
+    /// real one - if this topic's budget has room, or `None` if emitting
+    /// one now would exceed the topic's own rate limit.
+    pub fn maybe_chaff(&mut self) -> Option<Vec<u8>> {
+        if !self.budget.try_consume(1.0) {
+            return None;
+        }
+        // Two independent random lengths - chaff content and padding -
+        // so a chaff frame's total length follows the same distribution
+        // as a padded real frame's.
+        let chaff = random_bytes(random_padding_len());
+        Some(self.frame(&chaff, random_padding_len()))
+    }
+}
+
+impl IObfuscator for PaddedObfuscator {
+    fn obfuscate(&mut self, frame: &[u8]) -> Vec<u8> {
+        // Real traffic is never dropped to respect a cover-traffic budget;
+        // if padding budget is exhausted the envelope still ships, just
+        // without extra padding this time.
+        let padding_len = if self.budget.try_consume(1.0) {
+            random_padding_len()
+        } else {
+            0
+        };
+        self.frame(frame, padding_len)
+    }
+
+    fn deobfuscate(&mut self, wire: &[u8]) -> AriaResult<Vec<Vec<u8>>> {
+        self.recv_buffer.extend_from_slice(wire);
+        let mut frames = Vec::new();
+
+        loop {
+            if self.recv_buffer.len() < HEADER_LEN {
+                break;
+            }
+            let total_len =
+                u32::from_be_bytes(self.recv_buffer[0..4].try_into().unwrap()) as usize;
+            let real_len =
+                u32::from_be_bytes(self.recv_buffer[4..8].try_into().unwrap()) as usize;
+
+            if total_len < HEADER_LEN {
+                return Err(AriaError::Transport(format!(
+                    "obfuscated frame header claims a total length of {total_len}, shorter than the {HEADER_LEN}-byte header itself"
+                )));
+            }
+            if real_len > total_len.saturating_sub(HEADER_LEN) {
+                return Err(AriaError::Transport(
+                    "obfuscated frame header claims more payload than its own total length".into(),
+                ));
+            }
+            if self.recv_buffer.len() < total_len {
+                break; // rest of this frame hasn't arrived yet
+            }
+
+            frames.push(self.recv_buffer[HEADER_LEN..HEADER_LEN + real_len].to_vec());
+            self.recv_buffer.drain(0..total_len);
+        }
+
+        Ok(frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> QoSPolicy {
+        QoSPolicy {
+            max_rate_per_sec: 1000.0,
+            burst_size: 100,
+            max_queue_depth: 1000,
+        }
+    }
+
+    #[test]
+    fn test_null_obfuscator_passes_frames_through_unchanged() {
+        let mut obfs = NullObfuscator;
+        let wire = obfs.obfuscate(b"hello");
+        assert_eq!(obfs.deobfuscate(&wire).unwrap(), vec![b"hello".to_vec()]);
+    }
+
+    #[test]
+    fn test_padded_obfuscator_roundtrips_a_single_frame() {
+        let mut sender = PaddedObfuscator::new(&policy());
+        let mut receiver = PaddedObfuscator::new(&policy());
+
+        let wire = sender.obfuscate(b"telemetry-payload");
+        let frames = receiver.deobfuscate(&wire).unwrap();
+        assert_eq!(frames, vec![b"telemetry-payload".to_vec()]);
+    }
+
+    #[test]
+    fn test_padded_obfuscator_decouples_wire_size_from_payload_size() {
+        let mut sender = PaddedObfuscator::new(&policy());
+        let small = sender.obfuscate(b"a");
+        let large = sender.obfuscate(&vec![0u8; 4]);
+        // Both payloads are tiny but padding makes wire sizes unpredictable
+        // relative to the 1-byte/4-byte gap between them.
+        assert!(small.len() >= HEADER_LEN + 1);
+        assert!(large.len() >= HEADER_LEN + 4);
+    }
+
+    #[test]
+    fn test_deobfuscate_reassembles_a_frame_split_across_two_reads() {
+        let mut sender = PaddedObfuscator::new(&policy());
+        let mut receiver = PaddedObfuscator::new(&policy());
+
+        let wire = sender.obfuscate(b"split-me-please");
+        let midpoint = wire.len() / 2;
+
+        assert!(receiver.deobfuscate(&wire[..midpoint]).unwrap().is_empty());
+        let frames = receiver.deobfuscate(&wire[midpoint..]).unwrap();
+        assert_eq!(frames, vec![b"split-me-please".to_vec()]);
+    }
+
+    #[test]
+    fn test_deobfuscate_splits_two_frames_coalesced_into_one_read() {
+        let mut sender = PaddedObfuscator::new(&policy());
+        let mut receiver = PaddedObfuscator::new(&policy());
+
+        let mut wire = sender.obfuscate(b"first");
+        wire.extend_from_slice(&sender.obfuscate(b"second"));
+
+        let frames = receiver.deobfuscate(&wire).unwrap();
+        assert_eq!(frames, vec![b"first".to_vec(), b"second".to_vec()]);
+    }
+
+    #[test]
+    fn test_deobfuscate_rejects_a_total_length_shorter_than_the_header_itself() {
+        let mut receiver = PaddedObfuscator::new(&policy());
+        // total_len = 3, real_len = 0: both dodge the other guard, but a
+        // frame can never be shorter than its own 8-byte header, and this
+        // used to infinite-loop the parser instead of being rejected.
+        let mut malformed = vec![0u8; HEADER_LEN];
+        malformed[0..4].copy_from_slice(&3u32.to_be_bytes());
+        malformed[4..8].copy_from_slice(&0u32.to_be_bytes());
+        assert!(receiver.deobfuscate(&malformed).is_err());
+    }
+
+    #[test]
+    fn test_deobfuscate_rejects_a_header_claiming_more_payload_than_its_frame() {
+        let mut receiver = PaddedObfuscator::new(&policy());
+        let mut malformed = vec![0u8; HEADER_LEN + 4];
+        malformed[0..4].copy_from_slice(&(HEADER_LEN as u32 + 4).to_be_bytes());
+        malformed[4..8].copy_from_slice(&(HEADER_LEN as u32 + 100).to_be_bytes());
+        assert!(receiver.deobfuscate(&malformed).is_err());
+    }
+
+    #[test]
+    fn test_maybe_chaff_respects_an_exhausted_budget() {
+        let policy = QoSPolicy {
+            max_rate_per_sec: 0.0,
+            burst_size: 1,
+            max_queue_depth: 1000,
+        };
+        let mut obfs = PaddedObfuscator::new(&policy);
+        assert!(obfs.maybe_chaff().is_some());
+        assert!(obfs.maybe_chaff().is_none());
+    }
+
+    #[test]
+    fn test_obfuscate_still_ships_real_traffic_once_padding_budget_is_exhausted() {
+        let policy = QoSPolicy {
+            max_rate_per_sec: 0.0,
+            burst_size: 0,
+            max_queue_depth: 1000,
+        };
+        let mut sender = PaddedObfuscator::new(&policy);
+        let mut receiver = PaddedObfuscator::new(&policy);
+
+        let wire = sender.obfuscate(b"must-not-drop");
+        assert_eq!(
+            receiver.deobfuscate(&wire).unwrap(),
+            vec![b"must-not-drop".to_vec()]
+        );
+    }
+}
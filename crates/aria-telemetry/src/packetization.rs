@@ -1,6 +1,7 @@
 //! Packetization: fragmentation and defragmentation
 
-use aria_domain::{AriaError, AriaResult, Envelope, FragmentInfo};
+use crate::fec::ReedSolomonFec;
+use aria_domain::{AriaResult, Envelope, FecInfo, FragmentInfo, IFEC};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
@@ -8,41 +9,86 @@ const DEFAULT_MTU: usize = 1400;
 
 pub struct Packetizer {
     mtu: usize,
+    /// Parity shard count for FEC-protected fragmentation; `0` disables FEC and
+    /// falls back to plain fragmentation with no loss tolerance.
+    redundancy: usize,
 }
 
 impl Packetizer {
     pub fn new(mtu: usize) -> Self {
-        Self { mtu }
+        Self { mtu, redundancy: 0 }
     }
-    
-    pub fn fragment(&self, mut envelope: Envelope) -> AriaResult<Vec<Envelope>> {
+
+    /// Like [`Packetizer::new`], but each oversized payload is split into `k`
+    /// data shards (sized to `mtu`) plus `redundancy` Reed-Solomon parity
+    /// shards, so the `Defragmenter` can reconstruct the payload even if up
+    /// to `redundancy` of the `k + redundancy` fragments are lost.
+    pub fn with_redundancy(mtu: usize, redundancy: usize) -> Self {
+        Self { mtu, redundancy }
+    }
+
+    pub fn fragment(&self, envelope: Envelope) -> AriaResult<Vec<Envelope>> {
         let payload_size = envelope.payload.len();
-        
-        if payload_size <= self.mtu {
+
+        if payload_size <= self.mtu && self.redundancy == 0 {
             // No fragmentation needed
             return Ok(vec![envelope]);
         }
-        
+
+        if self.redundancy > 0 {
+            return self.fragment_with_fec(envelope);
+        }
+
         let num_fragments = (payload_size + self.mtu - 1) / self.mtu;
+        let group_id = envelope.id;
         let mut fragments = Vec::with_capacity(num_fragments);
-        
+
         for i in 0..num_fragments {
             let start = i * self.mtu;
             let end = std::cmp::min(start + self.mtu, payload_size);
             let fragment_payload = envelope.payload[start..end].to_vec();
-            
+
             let mut fragment = envelope.clone();
             fragment.id = uuid::Uuid::new_v4();
             fragment.payload = fragment_payload;
+            fragment.metadata.group_id = Some(group_id);
             fragment.metadata.fragment_info = Some(FragmentInfo {
                 fragment_id: i as u32,
                 total_fragments: num_fragments as u32,
                 fragment_offset: start,
             });
-            
+
             fragments.push(fragment);
         }
-        
+
+        Ok(fragments)
+    }
+
+    fn fragment_with_fec(&self, envelope: Envelope) -> AriaResult<Vec<Envelope>> {
+        let k = std::cmp::max(1, (envelope.payload.len() + self.mtu - 1) / self.mtu);
+        let m = self.redundancy;
+        let original_len = envelope.payload.len() as u32;
+        let group_id = envelope.id;
+
+        let fec = ReedSolomonFec;
+        let shards = fec.encode(&envelope.payload, k, m)?;
+
+        let mut fragments = Vec::with_capacity(k + m);
+        for (shard_index, shard) in shards.into_iter().enumerate() {
+            let mut fragment = envelope.clone();
+            fragment.id = uuid::Uuid::new_v4();
+            fragment.payload = shard;
+            fragment.metadata.group_id = Some(group_id);
+            fragment.metadata.fec_info = Some(FecInfo {
+                k: k as u32,
+                m: m as u32,
+                block_id: 0,
+                shard_index: shard_index as u32,
+                original_len,
+            });
+            fragments.push(fragment);
+        }
+
         Ok(fragments)
     }
 }
@@ -53,8 +99,11 @@ pub struct Defragmenter {
 }
 
 struct FragmentBuffer {
-    fragments: HashMap<u32, Vec<u8>>,
+    shards: HashMap<u32, Vec<u8>>,
+    /// Plain mode: all fragments required. FEC mode: `k` of `k + m` suffice.
+    needed: u32,
     total_fragments: u32,
+    fec: Option<(usize, usize, usize)>, // (k, m, original_len)
     original_envelope: Envelope,
     last_update: Instant,
 }
@@ -66,48 +115,88 @@ impl Defragmenter {
             timeout,
         }
     }
-    
+
     pub fn add_fragment(&mut self, envelope: Envelope) -> Option<Envelope> {
-        let frag_info = match &envelope.metadata.fragment_info {
-            Some(info) => info,
-            None => return Some(envelope), // Not a fragment
+        if envelope.metadata.fragment_info.is_none() && envelope.metadata.fec_info.is_none() {
+            return Some(envelope); // Not a fragment
+        }
+
+        let group_id = envelope.metadata.group_id.unwrap_or(envelope.id);
+
+        let (shard_index, payload) = if let Some(fec_info) = &envelope.metadata.fec_info {
+            (fec_info.shard_index, envelope.payload.clone())
+        } else {
+            let frag_info = envelope.metadata.fragment_info.as_ref().unwrap();
+            (frag_info.fragment_id, envelope.payload.clone())
         };
-        
-        let parent_id = envelope.id;
-        
-        let buffer = self.buffers.entry(parent_id).or_insert_with(|| {
-            FragmentBuffer {
-                fragments: HashMap::new(),
-                total_fragments: frag_info.total_fragments,
-                original_envelope: envelope.clone(),
-                last_update: Instant::now(),
+
+        let buffer = self.buffers.entry(group_id).or_insert_with(|| {
+            if let Some(fec_info) = &envelope.metadata.fec_info {
+                FragmentBuffer {
+                    shards: HashMap::new(),
+                    needed: fec_info.k,
+                    total_fragments: fec_info.k + fec_info.m,
+                    fec: Some((
+                        fec_info.k as usize,
+                        fec_info.m as usize,
+                        fec_info.original_len as usize,
+                    )),
+                    original_envelope: envelope.clone(),
+                    last_update: Instant::now(),
+                }
+            } else {
+                let frag_info = envelope.metadata.fragment_info.as_ref().unwrap();
+                FragmentBuffer {
+                    shards: HashMap::new(),
+                    needed: frag_info.total_fragments,
+                    total_fragments: frag_info.total_fragments,
+                    fec: None,
+                    original_envelope: envelope.clone(),
+                    last_update: Instant::now(),
+                }
             }
         });
-        
-        buffer.fragments.insert(frag_info.fragment_id, envelope.payload);
+
+        buffer.shards.insert(shard_index, payload);
         buffer.last_update = Instant::now();
-        
-        // Check if we have all fragments
-        if buffer.fragments.len() == buffer.total_fragments as usize {
-            // Reassemble
+
+        if buffer.shards.len() < buffer.needed as usize {
+            return None;
+        }
+
+        let payload = if let Some((k, m, original_len)) = buffer.fec {
+            let mut shards: Vec<Option<Vec<u8>>> = (0..buffer.total_fragments)
+                .map(|i| buffer.shards.get(&i).cloned())
+                .collect();
+            let fec = ReedSolomonFec;
+            let mut reconstructed = match fec.decode(&shards, k, m) {
+                Ok(data) => data,
+                Err(_) => {
+                    shards.clear();
+                    return None;
+                }
+            };
+            reconstructed.truncate(original_len);
+            reconstructed
+        } else {
             let mut payload = Vec::new();
             for i in 0..buffer.total_fragments {
-                if let Some(frag) = buffer.fragments.get(&i) {
-                    payload.extend_from_slice(frag);
-                }
+                payload.extend_from_slice(buffer.shards.get(&i)?);
             }
-            
-            let mut complete = buffer.original_envelope.clone();
-            complete.payload = payload;
-            complete.metadata.fragment_info = None;
-            
-            self.buffers.remove(&parent_id);
-            return Some(complete);
-        }
-        
-        None
+            payload
+        };
+
+        let mut complete = buffer.original_envelope.clone();
+        complete.id = group_id;
+        complete.payload = payload;
+        complete.metadata.group_id = None;
+        complete.metadata.fragment_info = None;
+        complete.metadata.fec_info = None;
+
+        self.buffers.remove(&group_id);
+        Some(complete)
     }
-    
+
     pub fn gc_expired(&mut self) {
         let now = Instant::now();
         self.buffers.retain(|_, buffer| {
@@ -133,14 +222,16 @@ mod tests {
             metadata: EnvelopeMetadata {
                 source_node: "test".into(),
                 sequence_number: 0,
+                group_id: None,
                 fragment_info: None,
                 fec_info: None,
                 crypto_info: None,
                 qos_class: "default".into(),
+                codec: CodecKind::Protobuf,
             },
         }
     }
-    
+
     #[test]
     fn test_no_fragmentation() {
         let packetizer = Packetizer::new(1400);
@@ -208,4 +299,63 @@ mod tests {
         let complete = result3.unwrap();
         assert_eq!(complete.payload, original_payload);
     }
+
+    #[test]
+    fn test_fec_fragmentation_shape() {
+        let packetizer = Packetizer::with_redundancy(1400, 2);
+        let envelope = make_envelope(3000);
+
+        let fragments = packetizer.fragment(envelope).unwrap();
+        // k = ceil(3000 / 1400) = 3 data shards + 2 parity shards
+        assert_eq!(fragments.len(), 5);
+        for fragment in &fragments {
+            let fec_info = fragment.metadata.fec_info.as_ref().unwrap();
+            assert_eq!(fec_info.k, 3);
+            assert_eq!(fec_info.m, 2);
+            assert_eq!(fec_info.original_len, 3000);
+        }
+    }
+
+    #[test]
+    fn test_fec_reassembly_with_dropped_shard() {
+        let packetizer = Packetizer::with_redundancy(1400, 2);
+        let original = make_envelope(3000);
+        let original_payload = original.payload.clone();
+
+        let fragments = packetizer.fragment(original).unwrap();
+        let mut defragmenter = Defragmenter::new(Duration::from_secs(10));
+
+        // Drop two of the five shards; k = 3 so reconstruction should still succeed.
+        let surviving: Vec<_> = fragments
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| *i != 1 && *i != 3)
+            .map(|(_, f)| f)
+            .collect();
+
+        let mut complete = None;
+        for fragment in surviving {
+            if let Some(envelope) = defragmenter.add_fragment(fragment) {
+                complete = Some(envelope);
+            }
+        }
+
+        let complete = complete.expect("should reconstruct from k surviving shards");
+        assert_eq!(complete.payload, original_payload);
+    }
+
+    #[test]
+    fn test_fec_fragmentation_empty_payload_does_not_panic() {
+        let packetizer = Packetizer::with_redundancy(1400, 2);
+        let envelope = make_envelope(0);
+
+        let fragments = packetizer.fragment(envelope).unwrap();
+        assert_eq!(fragments.len(), 3); // k = 1 data shard + 2 parity shards
+        for fragment in &fragments {
+            let fec_info = fragment.metadata.fec_info.as_ref().unwrap();
+            assert_eq!(fec_info.k, 1);
+            assert_eq!(fec_info.m, 2);
+            assert_eq!(fec_info.original_len, 0);
+        }
+    }
 }
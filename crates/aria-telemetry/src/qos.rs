@@ -150,10 +150,12 @@ mod tests {
             metadata: EnvelopeMetadata {
                 source_node: "test".into(),
                 sequence_number: seq,
+                group_id: None,
                 fragment_info: None,
                 fec_info: None,
                 crypto_info: None,
                 qos_class: "default".into(),
+                codec: CodecKind::Protobuf,
             },
         }
     }
@@ -0,0 +1,637 @@
+//! FROST threshold Schnorr signatures over Ristretto25519.
+//!
+//! A single `CryptoBox` signature is one point of compromise: whoever holds
+//! that `SigningKey` can authorize any command. This module lets a t-of-n
+//! quorum of ARIA nodes jointly produce one compact signature that verifiers
+//! check against a single group key, with no individual node ever holding
+//! (or needing to reconstruct) the group's private key.
+//!
+//! Key generation has two paths:
+//!
+//! - `DkgParticipant` is the production path: an interactive Pedersen/Feldman
+//!   VSS round. Every participant deals shares of its *own* secret
+//!   polynomial instead of one party dealing the whole group secret, so no
+//!   single process - not even transiently, not even one trusted with
+//!   equivalent material - ever assembles the group private key. Each
+//!   recipient verifies its share against the sender's published Feldman
+//!   commitment before accepting it, so a dishonest dealer handing out an
+//!   inconsistent share is caught rather than silently corrupting that
+//!   participant's key. This omits the complaint/justification sub-protocol
+//!   a full Pedersen VSS round would add for publicly attributing *whose*
+//!   share was bad when verification fails; that's future work if a
+//!   deployment needs it, tracked the same way the rest of this module's
+//!   scope gaps are.
+//! - `TrustedDealerKeyGen::generate` Shamir-shares a fresh random group
+//!   secret from a single process. Kept for tests/demos where standing up
+//!   the interactive protocol's multi-party exchange isn't worth it; **not**
+//!   the production key-establishment path for a new deployment, since that
+//!   process briefly holds the whole group secret in memory before it's
+//!   split - the exact single-point-of-compromise `DkgParticipant` exists to
+//!   avoid.
+//!
+//! Signing is the standard two-round FROST protocol: `round1` publishes a
+//! hiding/binding nonce commitment pair, `round2` consumes the full set of
+//! commitments plus the message to compute a signature share, and
+//! `aggregate` sums the shares of any `threshold`-sized quorum into a
+//! verifiable Schnorr `(R, z)` signature.
+
+use aria_domain::{AriaError, AriaResult};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha512};
+
+/// The group's public key, shared by every participant and by verifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GroupPublicKey(pub CompressedRistretto);
+
+/// One participant's share of the group secret, produced by
+/// [`DkgParticipant::round2`] (production path) or
+/// [`TrustedDealerKeyGen::generate`] (tests/demos). Feeds a
+/// [`ThresholdSigner`]; never sent over the wire.
+#[derive(Clone)]
+pub struct KeyShare {
+    pub participant_id: u16,
+    pub threshold: u16,
+    pub group_public_key: GroupPublicKey,
+    secret: Scalar,
+}
+
+/// Trusted-dealer FROST key generation. See the module-level scope note:
+/// this process itself holds the group secret in plaintext for the
+/// duration of `generate`, which is a different trust model than an
+/// interactive DKG and needs explicit security sign-off before it's relied
+/// on as a deployment's real key-establishment path.
+pub struct TrustedDealerKeyGen;
+
+impl TrustedDealerKeyGen {
+    /// Shamir-shares a fresh random group secret across `n` participants
+    /// numbered `1..=n`, any `threshold` of whom can later jointly sign
+    /// (without ever reconstructing the secret itself) via
+    /// [`ThresholdSigner`]. Trusted-dealer: the caller's own process
+    /// briefly holds the undivided group secret in `coefficients[0]`
+    /// before it's shared out below.
+    pub fn generate(threshold: u16, n: u16) -> AriaResult<Vec<KeyShare>> {
+        if threshold == 0 || threshold > n {
+            return Err(AriaError::Crypto(format!(
+                "invalid threshold {threshold} of {n} participants"
+            )));
+        }
+
+        // f(x) = secret + a_1*x + ... + a_{t-1}*x^{t-1}; f(0) is the group
+        // secret, f(i) is participant i's share.
+        let coefficients: Vec<Scalar> = (0..threshold).map(|_| random_scalar()).collect();
+        let group_public_key =
+            GroupPublicKey((coefficients[0] * RISTRETTO_BASEPOINT_POINT).compress());
+
+        Ok((1..=n)
+            .map(|participant_id| KeyShare {
+                participant_id,
+                threshold,
+                group_public_key,
+                secret: evaluate_polynomial(&coefficients, Scalar::from(participant_id as u64)),
+            })
+            .collect())
+    }
+}
+
+/// Round-1 broadcast of the interactive DKG: `participant_id`'s Feldman
+/// commitments to its secret polynomial's coefficients,
+/// `[a_0*G, a_1*G, ..., a_{t-1}*G]`. Published to every other participant;
+/// contains no secret material, unlike the private shares `share_for`
+/// produces.
+#[derive(Clone)]
+pub struct DkgCommitment {
+    pub participant_id: u16,
+    coefficients: Vec<CompressedRistretto>,
+}
+
+/// One participant's state while running the interactive Pedersen/Feldman
+/// VSS key-generation protocol. `round1` publishes this participant's
+/// commitment; `share_for` hands out this participant's private share for
+/// one other participant (send it over a confidential, authenticated
+/// channel - e.g. a `CryptoBox` session - never broadcast alongside
+/// `round1`'s commitment); `round2` verifies every share this participant
+/// received and combines them into its final `KeyShare`. No participant,
+/// including this one, ever learns the group secret or any other
+/// participant's polynomial - see the module-level doc comment for how this
+/// differs from `TrustedDealerKeyGen`.
+pub struct DkgParticipant {
+    participant_id: u16,
+    threshold: u16,
+    coefficients: Vec<Scalar>,
+}
+
+impl DkgParticipant {
+    /// Samples this participant's own degree-`threshold - 1` polynomial.
+    /// `threshold` must agree across every participant in the run; `round2`
+    /// checks it against the number of commitments it was given.
+    pub fn new(participant_id: u16, threshold: u16) -> Self {
+        Self {
+            participant_id,
+            threshold,
+            coefficients: (0..threshold).map(|_| random_scalar()).collect(),
+        }
+    }
+
+    /// Round 1: commits to this participant's polynomial without revealing
+    /// it. Broadcast the result to every other participant.
+    pub fn round1(&self) -> DkgCommitment {
+        DkgCommitment {
+            participant_id: self.participant_id,
+            coefficients: self
+                .coefficients
+                .iter()
+                .map(|c| (*c * RISTRETTO_BASEPOINT_POINT).compress())
+                .collect(),
+        }
+    }
+
+    /// This participant's private share for `recipient_id` - `f(recipient_id)`
+    /// where `f` is this participant's own polynomial. Send it to that
+    /// participant directly; it must never be broadcast the way `round1`'s
+    /// commitment is.
+    pub fn share_for(&self, recipient_id: u16) -> Scalar {
+        evaluate_polynomial(&self.coefficients, Scalar::from(recipient_id as u64))
+    }
+
+    /// Round 2: verifies every received share against its sender's `round1`
+    /// commitment via Feldman's check (`share * G == sum_k C_k *
+    /// my_id^k`), then sums the verified shares - including this
+    /// participant's own `share_for(self.participant_id)` - into this
+    /// participant's final secret share. The group public key is the sum of
+    /// every participant's constant-term commitment, so it's derivable by
+    /// anyone from public data alone, without any party ever holding the
+    /// scalar it corresponds to.
+    ///
+    /// `commitments` must include one entry per participant in the run
+    /// (this participant's own included), and `received_shares` the matching
+    /// share from each.
+    pub fn round2(
+        self,
+        commitments: &[DkgCommitment],
+        received_shares: &[(u16, Scalar)],
+    ) -> AriaResult<KeyShare> {
+        let n = commitments.len() as u16;
+        if self.threshold == 0 || self.threshold > n {
+            return Err(AriaError::Crypto(format!(
+                "invalid threshold {} of {n} participants",
+                self.threshold
+            )));
+        }
+
+        let my_id = Scalar::from(self.participant_id as u64);
+        let mut secret = Scalar::ZERO;
+        let mut group_public_key: Option<RistrettoPoint> = None;
+
+        for commitment in commitments {
+            let share = received_shares
+                .iter()
+                .find(|(id, _)| *id == commitment.participant_id)
+                .map(|(_, share)| *share)
+                .ok_or_else(|| {
+                    AriaError::Crypto(format!(
+                        "missing share from participant {}",
+                        commitment.participant_id
+                    ))
+                })?;
+
+            let mut expected: Option<RistrettoPoint> = None;
+            let mut power = Scalar::ONE;
+            for c in &commitment.coefficients {
+                let point = c.decompress().ok_or_else(|| {
+                    AriaError::Crypto("malformed DKG commitment point".into())
+                })?;
+                let term = power * point;
+                expected = Some(match expected {
+                    Some(acc) => acc + term,
+                    None => term,
+                });
+                power = power * my_id;
+            }
+            let expected = expected
+                .ok_or_else(|| AriaError::Crypto("commitment has no coefficients".into()))?;
+
+            if share * RISTRETTO_BASEPOINT_POINT != expected {
+                return Err(AriaError::Crypto(format!(
+                    "participant {}'s share failed Feldman verification",
+                    commitment.participant_id
+                )));
+            }
+
+            secret = secret + share;
+
+            let constant = commitment.coefficients[0].decompress().ok_or_else(|| {
+                AriaError::Crypto("malformed DKG commitment point".into())
+            })?;
+            group_public_key = Some(match group_public_key {
+                Some(acc) => acc + constant,
+                None => constant,
+            });
+        }
+
+        Ok(KeyShare {
+            participant_id: self.participant_id,
+            threshold: self.threshold,
+            group_public_key: GroupPublicKey(
+                group_public_key
+                    .ok_or_else(|| AriaError::Crypto("no commitments to aggregate".into()))?
+                    .compress(),
+            ),
+            secret,
+        })
+    }
+}
+
+fn evaluate_polynomial(coefficients: &[Scalar], x: Scalar) -> Scalar {
+    coefficients
+        .iter()
+        .rev()
+        .fold(Scalar::ZERO, |acc, c| acc * x + c)
+}
+
+fn random_scalar() -> Scalar {
+    let mut bytes = [0u8; 64];
+    OsRng.fill_bytes(&mut bytes);
+    Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+fn hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    Scalar::from_hash(hasher)
+}
+
+/// Lagrange coefficient for `id`, interpolating at x=0 over `participants`.
+fn lagrange_coefficient(id: u16, participants: &[u16]) -> Scalar {
+    let xi = Scalar::from(id as u64);
+    participants
+        .iter()
+        .filter(|&&j| j != id)
+        .fold(Scalar::ONE, |acc, &j| {
+            let xj = Scalar::from(j as u64);
+            acc * xj * (xj - xi).invert()
+        })
+}
+
+/// A signer's round-1 nonce commitment, published to the rest of the
+/// signing quorum before anyone learns the binding factor.
+#[derive(Debug, Clone, Copy)]
+pub struct NonceCommitment {
+    pub participant_id: u16,
+    hiding: CompressedRistretto,
+    binding: CompressedRistretto,
+}
+
+/// Round-1 secret nonces. Held only in memory between `round1` and
+/// `round2`; never serialized or sent anywhere.
+struct SigningNonces {
+    hiding: Scalar,
+    binding: Scalar,
+}
+
+/// This signer's contribution to a threshold signature over one message,
+/// returned by `round2` and consumed by `aggregate`.
+#[derive(Debug, Clone, Copy)]
+pub struct SignatureShare {
+    pub participant_id: u16,
+    z: Scalar,
+}
+
+/// Drives one participant through a single FROST signing round: `round1`
+/// then `round2` then (on whichever signer collects every share)
+/// `aggregate`. Not reusable across messages - construct a fresh
+/// `ThresholdSigner` from the same `KeyShare` for the next signature.
+pub struct ThresholdSigner {
+    share: KeyShare,
+    nonces: Option<SigningNonces>,
+    group_commitment: Option<RistrettoPoint>,
+}
+
+impl ThresholdSigner {
+    pub fn new(share: KeyShare) -> Self {
+        Self {
+            share,
+            nonces: None,
+            group_commitment: None,
+        }
+    }
+
+    /// Round 1: generates this signer's hiding/binding nonce pair and
+    /// returns the commitment to publish to the rest of the quorum. Must be
+    /// called exactly once before `round2`.
+    pub fn round1(&mut self) -> NonceCommitment {
+        let hiding = random_scalar();
+        let binding = random_scalar();
+        self.nonces = Some(SigningNonces { hiding, binding });
+        NonceCommitment {
+            participant_id: self.share.participant_id,
+            hiding: (hiding * RISTRETTO_BASEPOINT_POINT).compress(),
+            binding: (binding * RISTRETTO_BASEPOINT_POINT).compress(),
+        }
+    }
+
+    /// Round 2: given every signer's round-1 commitment (including this
+    /// signer's own) and the message, computes this signer's binding factor
+    /// `rho_i = H(i, msg, commitment_list)`, its Lagrange coefficient over
+    /// the participant set, and returns its signature share.
+    pub fn round2(
+        &mut self,
+        msg: &[u8],
+        commitments: &[NonceCommitment],
+    ) -> AriaResult<SignatureShare> {
+        if commitments.len() < self.share.threshold as usize {
+            return Err(AriaError::Crypto(format!(
+                "insufficient signers: got {} commitments, need at least {}",
+                commitments.len(),
+                self.share.threshold
+            )));
+        }
+
+        let nonces = self
+            .nonces
+            .take()
+            .ok_or_else(|| AriaError::Crypto("round2 called before round1".into()))?;
+
+        if !commitments
+            .iter()
+            .any(|c| c.participant_id == self.share.participant_id)
+        {
+            return Err(AriaError::Crypto(
+                "commitment list is missing this signer's own round-1 commitment".into(),
+            ));
+        }
+
+        let group_commitment = group_commitment(msg, commitments)?;
+        let challenge = compute_challenge(&group_commitment, &self.share.group_public_key, msg);
+
+        let rho_i = binding_factor(self.share.participant_id, msg, commitments);
+        let r_i = nonces.hiding + rho_i * nonces.binding;
+
+        let participants: Vec<u16> = commitments.iter().map(|c| c.participant_id).collect();
+        let lambda_i = lagrange_coefficient(self.share.participant_id, &participants);
+
+        self.group_commitment = Some(group_commitment);
+
+        Ok(SignatureShare {
+            participant_id: self.share.participant_id,
+            z: r_i + lambda_i * challenge * self.share.secret,
+        })
+    }
+
+    /// Sums signature shares from a `round2` quorum into a standard Schnorr
+    /// signature `R (32 bytes) || z (32 bytes)`, verifiable against the
+    /// group key via [`verify_group`]. Must be called on a signer that has
+    /// already completed `round2` for this message, since that's where the
+    /// group nonce commitment `R` is computed.
+    pub fn aggregate(&self, shares: &[SignatureShare]) -> AriaResult<Vec<u8>> {
+        let r = self
+            .group_commitment
+            .ok_or_else(|| AriaError::Crypto("aggregate called before round2".into()))?;
+
+        if shares.len() < self.share.threshold as usize {
+            return Err(AriaError::Crypto(format!(
+                "insufficient signers: got {} shares, need at least {}",
+                shares.len(),
+                self.share.threshold
+            )));
+        }
+
+        let z: Scalar = shares.iter().map(|s| s.z).sum();
+
+        let mut sig = Vec::with_capacity(64);
+        sig.extend_from_slice(r.compress().as_bytes());
+        sig.extend_from_slice(z.as_bytes());
+        Ok(sig)
+    }
+}
+
+fn binding_factor(id: u16, msg: &[u8], commitments: &[NonceCommitment]) -> Scalar {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&id.to_be_bytes());
+    bytes.extend_from_slice(msg);
+    for c in commitments {
+        bytes.extend_from_slice(&c.participant_id.to_be_bytes());
+        bytes.extend_from_slice(c.hiding.as_bytes());
+        bytes.extend_from_slice(c.binding.as_bytes());
+    }
+    hash_to_scalar(&[&bytes])
+}
+
+fn group_commitment(msg: &[u8], commitments: &[NonceCommitment]) -> AriaResult<RistrettoPoint> {
+    let mut total: Option<RistrettoPoint> = None;
+    for c in commitments {
+        let hiding_point = c
+            .hiding
+            .decompress()
+            .ok_or_else(|| AriaError::Crypto("malformed hiding commitment point".into()))?;
+        let binding_point = c
+            .binding
+            .decompress()
+            .ok_or_else(|| AriaError::Crypto("malformed binding commitment point".into()))?;
+        let rho_i = binding_factor(c.participant_id, msg, commitments);
+        let term = hiding_point + rho_i * binding_point;
+        total = Some(match total {
+            Some(acc) => acc + term,
+            None => term,
+        });
+    }
+    total.ok_or_else(|| AriaError::Crypto("no commitments to aggregate".into()))
+}
+
+fn compute_challenge(r: &RistrettoPoint, group_public_key: &GroupPublicKey, msg: &[u8]) -> Scalar {
+    hash_to_scalar(&[r.compress().as_bytes(), group_public_key.0.as_bytes(), msg])
+}
+
+/// Verifies a FROST-aggregated signature `sig` (`R || z`, 64 bytes) over
+/// `data` against a group's public key, using the same single-signature
+/// verify-style API as `ICryptoBox::verify`.
+pub fn verify_group(data: &[u8], sig: &[u8], group_key: &GroupPublicKey) -> AriaResult<bool> {
+    if sig.len() != 64 {
+        return Err(AriaError::Crypto(format!(
+            "malformed group signature: expected 64 bytes, got {}",
+            sig.len()
+        )));
+    }
+
+    let r_bytes: [u8; 32] = sig[..32].try_into().expect("checked length above");
+    let r = CompressedRistretto(r_bytes)
+        .decompress()
+        .ok_or_else(|| AriaError::Crypto("R is not a valid Ristretto point".into()))?;
+
+    let z_bytes: [u8; 32] = sig[32..].try_into().expect("checked length above");
+    let z: Scalar = Option::from(Scalar::from_canonical_bytes(z_bytes))
+        .ok_or_else(|| AriaError::Crypto("z is not a canonical scalar".into()))?;
+
+    let y = group_key
+        .0
+        .decompress()
+        .ok_or_else(|| AriaError::Crypto("group key is not a valid Ristretto point".into()))?;
+
+    let challenge = compute_challenge(&r, group_key, data);
+    Ok(z * RISTRETTO_BASEPOINT_POINT == r + challenge * y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign_with_quorum(
+        shares: &[KeyShare],
+        signer_ids: &[u16],
+        msg: &[u8],
+    ) -> (Vec<u8>, GroupPublicKey) {
+        let group_public_key = shares[0].group_public_key;
+        let mut signers: Vec<ThresholdSigner> = signer_ids
+            .iter()
+            .map(|id| ThresholdSigner::new(shares[*id as usize - 1].clone()))
+            .collect();
+
+        let commitments: Vec<NonceCommitment> =
+            signers.iter_mut().map(|s| s.round1()).collect();
+
+        let shares: Vec<SignatureShare> = signers
+            .iter_mut()
+            .map(|s| s.round2(msg, &commitments).unwrap())
+            .collect();
+
+        let sig = signers[0].aggregate(&shares).unwrap();
+        (sig, group_public_key)
+    }
+
+    #[test]
+    fn test_threshold_quorum_produces_a_verifiable_group_signature() {
+        let shares = TrustedDealerKeyGen::generate(2, 3).unwrap();
+        let msg = b"emergency-stop-override";
+        let (sig, group_key) = sign_with_quorum(&shares, &[1, 3], msg);
+        assert!(verify_group(msg, &sig, &group_key).unwrap());
+    }
+
+    #[test]
+    fn test_any_qualifying_subset_of_signers_produces_a_valid_signature() {
+        let shares = TrustedDealerKeyGen::generate(2, 3).unwrap();
+        let msg = b"waypoint-update";
+        let (sig_a, group_key) = sign_with_quorum(&shares, &[1, 2], msg);
+        let (sig_b, _) = sign_with_quorum(&shares, &[2, 3], msg);
+        assert!(verify_group(msg, &sig_a, &group_key).unwrap());
+        assert!(verify_group(msg, &sig_b, &group_key).unwrap());
+    }
+
+    #[test]
+    fn test_tampered_signature_is_rejected() {
+        let shares = TrustedDealerKeyGen::generate(2, 3).unwrap();
+        let msg = b"disarm-payload";
+        let (mut sig, group_key) = sign_with_quorum(&shares, &[1, 2], msg);
+        *sig.last_mut().unwrap() ^= 0x01;
+        assert!(!verify_group(msg, &sig, &group_key).unwrap());
+    }
+
+    #[test]
+    fn test_signature_does_not_verify_against_a_different_group_key() {
+        let shares_a = TrustedDealerKeyGen::generate(2, 3).unwrap();
+        let shares_b = TrustedDealerKeyGen::generate(2, 3).unwrap();
+        let msg = b"return-to-base";
+        let (sig, _) = sign_with_quorum(&shares_a, &[1, 2], msg);
+        assert!(!verify_group(msg, &sig, &shares_b[0].group_public_key).unwrap());
+    }
+
+    #[test]
+    fn test_round2_rejects_a_commitment_set_below_the_threshold() {
+        let shares = TrustedDealerKeyGen::generate(3, 5).unwrap();
+        let mut signer = ThresholdSigner::new(shares[0].clone());
+        let commitment = signer.round1();
+        let result = signer.round2(b"msg", &[commitment]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_round2_rejects_a_commitment_list_missing_this_signers_own() {
+        let shares = TrustedDealerKeyGen::generate(2, 3).unwrap();
+        let mut signer_a = ThresholdSigner::new(shares[0].clone());
+        let mut signer_b = ThresholdSigner::new(shares[1].clone());
+        let mut signer_c = ThresholdSigner::new(shares[2].clone());
+
+        let commitment_b = signer_b.round1();
+        let commitment_c = signer_c.round1();
+        signer_a.round1();
+
+        // Signer A's own commitment isn't in this list.
+        let result = signer_a.round2(b"msg", &[commitment_b, commitment_c]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_generate_rejects_a_threshold_above_participant_count() {
+        let result = TrustedDealerKeyGen::generate(4, 3);
+        assert!(result.is_err());
+    }
+
+    /// Runs the full interactive DKG for `n` participants numbered `1..=n`,
+    /// exchanging every participant's round-1 commitment and private shares
+    /// out of band (as a real deployment would over per-pair `CryptoBox`
+    /// sessions) before every participant runs `round2`.
+    fn run_dkg(threshold: u16, n: u16) -> Vec<KeyShare> {
+        let dealers: Vec<DkgParticipant> =
+            (1..=n).map(|id| DkgParticipant::new(id, threshold)).collect();
+        let commitments: Vec<DkgCommitment> = dealers.iter().map(|d| d.round1()).collect();
+
+        // shares_for[recipient - 1] holds the (sender_id, share) pair every
+        // dealer privately sent `recipient`, as if exchanged over per-pair
+        // channels rather than broadcast alongside the round-1 commitments.
+        let mut shares_for: Vec<Vec<(u16, Scalar)>> = vec![Vec::new(); n as usize];
+        for dealer in &dealers {
+            for recipient_id in 1..=n {
+                shares_for[recipient_id as usize - 1]
+                    .push((dealer.participant_id, dealer.share_for(recipient_id)));
+            }
+        }
+
+        dealers
+            .into_iter()
+            .map(|dealer| {
+                let received = &shares_for[dealer.participant_id as usize - 1];
+                dealer.round2(&commitments, received).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dkg_quorum_produces_a_verifiable_group_signature() {
+        let shares = run_dkg(2, 3);
+        let msg = b"emergency-stop-override";
+        let (sig, group_key) = sign_with_quorum(&shares, &[1, 3], msg);
+        assert!(verify_group(msg, &sig, &group_key).unwrap());
+    }
+
+    #[test]
+    fn test_dkg_rejects_a_tampered_share() {
+        let dealers: Vec<DkgParticipant> =
+            (1..=3u16).map(|id| DkgParticipant::new(id, 2)).collect();
+        let commitments: Vec<DkgCommitment> = dealers.iter().map(|d| d.round1()).collect();
+
+        let mut received: Vec<(u16, Scalar)> = dealers
+            .iter()
+            .map(|d| (d.participant_id, d.share_for(1)))
+            .collect();
+        // Corrupt the share participant 2 claims to have sent participant 1.
+        received[1].1 = received[1].1 + Scalar::ONE;
+
+        let result = dealers.into_iter().next().unwrap().round2(&commitments, &received);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_dkg_no_single_participant_ever_holds_the_group_secret() {
+        // Each dealer only ever samples its own polynomial and sees other
+        // participants' public commitments and shares - never another
+        // participant's coefficients, so there's no point in the protocol
+        // where the combined group secret exists in one place.
+        let dealer = DkgParticipant::new(1, 2);
+        assert_eq!(dealer.coefficients.len(), 2);
+    }
+}
@@ -1,15 +1,27 @@
 //! Transport implementations: QUIC, MQTT-SN, DTN
 
-use aria_domain::{AriaResult, Envelope, ITransport};
+use crate::obfs::NullObfuscator;
+use crate::packetization::Defragmenter;
+use aria_domain::{AriaResult, Envelope, IObfuscator, ITransport};
 use async_trait::async_trait;
 use quinn::{Endpoint, ServerConfig};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
+/// How long an incomplete fragment/FEC-shard reassembly buffer is kept
+/// before `QuicTransport::run_receive_loop` gives up on it. Mirrors
+/// `Defragmenter`'s own `gc_expired` convention.
+const REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often the receive loop sweeps for expired reassembly buffers.
+const REASSEMBLY_GC_INTERVAL: Duration = Duration::from_secs(5);
+
 pub struct QuicTransport {
     endpoint: Option<Endpoint>,
     rx_channel: Option<mpsc::Receiver<Envelope>>,
     tx_channel: Option<mpsc::Sender<Envelope>>,
+    handler: Option<Arc<dyn Fn(Envelope) + Send + Sync>>,
+    obfuscator: Box<dyn IObfuscator>,
 }
 
 impl QuicTransport {
@@ -18,23 +30,102 @@ impl QuicTransport {
             endpoint: None,
             rx_channel: None,
             tx_channel: None,
+            handler: None,
+            obfuscator: Box::new(NullObfuscator),
+        }
+    }
+
+    /// Installs the wire-framing obfuscator `send`/`receive_wire_bytes`
+    /// apply at the qos/transport boundary. Defaults to `NullObfuscator`,
+    /// since DPI-resistant framing is opt-in per topic.
+    pub fn set_obfuscator(&mut self, obfuscator: Box<dyn IObfuscator>) {
+        self.obfuscator = obfuscator;
+    }
+
+    /// Deobfuscates bytes read directly off the wire back into zero or more
+    /// envelope payloads, re-segmenting across reads as needed. The real
+    /// QUIC read loop would call this before envelope deserialization;
+    /// exposed as its own method since that socket plumbing is still a
+    /// stub here (see `send`).
+    pub fn receive_wire_bytes(&mut self, wire: &[u8]) -> AriaResult<Vec<Vec<u8>>> {
+        self.obfuscator.deobfuscate(wire)
+    }
+
+    /// Test/loopback constructor: wires an mpsc channel straight into
+    /// `rx_channel` so `run_receive_loop` can be driven without a real QUIC
+    /// socket underneath it.
+    #[cfg(test)]
+    pub fn with_loopback() -> (Self, mpsc::Sender<Envelope>) {
+        let (tx, rx) = mpsc::channel(64);
+        let mut transport = Self::new();
+        transport.rx_channel = Some(rx);
+        (transport, tx)
+    }
+
+    /// Drains `rx_channel`, reassembling fragmented/FEC-sharded envelopes via
+    /// `Defragmenter` and dispatching each completed one to the registered
+    /// `on_receive` handler, highest `Priority` first (`P0` before `P3`),
+    /// then by `topic`. Returns once the channel closes or no handler was
+    /// ever registered.
+    pub async fn run_receive_loop(&mut self) {
+        let Some(mut rx) = self.rx_channel.take() else {
+            tracing::warn!("QUIC receive loop started with no rx_channel wired up");
+            return;
+        };
+        let Some(handler) = self.handler.clone() else {
+            tracing::warn!("QUIC receive loop started with no handler registered");
+            return;
+        };
+
+        let mut defragmenter = Defragmenter::new(REASSEMBLY_TIMEOUT);
+        let mut gc_interval = tokio::time::interval(REASSEMBLY_GC_INTERVAL);
+
+        loop {
+            tokio::select! {
+                received = rx.recv() => {
+                    let Some(envelope) = received else { break; };
+                    let mut batch = Vec::new();
+                    if let Some(complete) = defragmenter.add_fragment(envelope) {
+                        batch.push(complete);
+                    }
+                    while let Ok(envelope) = rx.try_recv() {
+                        if let Some(complete) = defragmenter.add_fragment(envelope) {
+                            batch.push(complete);
+                        }
+                    }
+                    dispatch_order(&mut batch);
+                    for envelope in batch {
+                        handler(envelope);
+                    }
+                }
+                _ = gc_interval.tick() => {
+                    defragmenter.gc_expired();
+                }
+            }
         }
     }
 }
 
+/// Sorts a batch of simultaneously-ready envelopes so `P0` is dispatched
+/// ahead of `P3`, and same-priority envelopes are grouped by `topic`.
+fn dispatch_order(batch: &mut [Envelope]) {
+    batch.sort_by(|a, b| a.priority.cmp(&b.priority).then_with(|| a.topic.cmp(&b.topic)));
+}
+
 #[async_trait]
 impl ITransport for QuicTransport {
     async fn send(&mut self, envelope: Envelope) -> AriaResult<()> {
         // Serialize and send via QUIC
-        tracing::debug!("QUIC send: {:?}", envelope.id);
+        let wire = self.obfuscator.obfuscate(&envelope.payload);
+        tracing::debug!("QUIC send: {:?} ({} wire bytes)", envelope.id, wire.len());
         Ok(())
     }
-    
+
     async fn on_receive(&mut self, handler: Box<dyn Fn(Envelope) + Send + Sync>) {
-        // Register handler
+        self.handler = Some(Arc::from(handler));
         tracing::debug!("QUIC receive handler registered");
     }
-    
+
     async fn connect(&mut self, endpoint: &str) -> AriaResult<()> {
         tracing::info!("QUIC connecting to {}", endpoint);
         // Create QUIC client endpoint
@@ -131,16 +222,155 @@ impl ITransport for DtnTransport {
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+    use aria_domain::*;
+    use chrono::Utc;
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
     #[tokio::test]
     async fn test_quic_transport() {
         let mut transport = QuicTransport::new();
         assert_eq!(transport.name(), "QUIC");
     }
-    
+
+    #[test]
+    fn test_default_obfuscator_is_a_pass_through() {
+        let mut transport = QuicTransport::new();
+        assert_eq!(transport.receive_wire_bytes(b"raw").unwrap(), vec![b"raw".to_vec()]);
+    }
+
+    #[test]
+    fn test_receive_wire_bytes_deobfuscates_using_the_configured_obfuscator() {
+        let policy = QoSPolicy {
+            max_rate_per_sec: 1000.0,
+            burst_size: 100,
+            max_queue_depth: 1000,
+        };
+        let wire = crate::obfs::PaddedObfuscator::new(&policy).obfuscate(b"payload-bytes");
+
+        let mut transport = QuicTransport::new();
+        transport.set_obfuscator(Box::new(crate::obfs::PaddedObfuscator::new(&policy)));
+
+        let frames = transport.receive_wire_bytes(&wire).unwrap();
+        assert_eq!(frames, vec![b"payload-bytes".to_vec()]);
+    }
+
+    #[tokio::test]
+    async fn test_send_runs_outgoing_payload_through_the_configured_obfuscator() {
+        let policy = QoSPolicy {
+            max_rate_per_sec: 1000.0,
+            burst_size: 100,
+            max_queue_depth: 1000,
+        };
+        let mut transport = QuicTransport::new();
+        transport.set_obfuscator(Box::new(crate::obfs::PaddedObfuscator::new(&policy)));
+        assert!(transport.send(make_envelope(Priority::P1, "secure")).await.is_ok());
+    }
+
     #[tokio::test]
     async fn test_dtn_store_and_forward() {
         let mut transport = DtnTransport::new();
         assert_eq!(transport.store.len(), 0);
     }
+
+    fn make_envelope(priority: Priority, topic: &str) -> Envelope {
+        Envelope {
+            id: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            schema_id: 1,
+            priority,
+            topic: topic.into(),
+            payload: b"payload".to_vec(),
+            metadata: EnvelopeMetadata {
+                source_node: "peer".into(),
+                sequence_number: 0,
+                group_id: None,
+                fragment_info: None,
+                fec_info: None,
+                crypto_info: None,
+                qos_class: "default".into(),
+                codec: CodecKind::Protobuf,
+            },
+        }
+    }
+
+    #[test]
+    fn test_dispatch_order_ranks_p0_ahead_of_p3() {
+        let mut batch = vec![
+            make_envelope(Priority::P3, "logs"),
+            make_envelope(Priority::P0, "cmd"),
+            make_envelope(Priority::P1, "state"),
+        ];
+        dispatch_order(&mut batch);
+        let topics: Vec<_> = batch.iter().map(|e| e.topic.as_str()).collect();
+        assert_eq!(topics, vec!["cmd", "state", "logs"]);
+    }
+
+    #[test]
+    fn test_dispatch_order_breaks_ties_by_topic() {
+        let mut batch = vec![
+            make_envelope(Priority::P1, "zeta"),
+            make_envelope(Priority::P1, "alpha"),
+        ];
+        dispatch_order(&mut batch);
+        let topics: Vec<_> = batch.iter().map(|e| e.topic.as_str()).collect();
+        assert_eq!(topics, vec!["alpha", "zeta"]);
+    }
+
+    #[tokio::test]
+    async fn test_receive_loop_dispatches_batch_in_priority_order() {
+        let (mut transport, tx) = QuicTransport::with_loopback();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_clone = received.clone();
+        transport
+            .on_receive(Box::new(move |envelope: Envelope| {
+                received_clone.lock().unwrap().push(envelope.topic.clone());
+            }))
+            .await;
+
+        tx.send(make_envelope(Priority::P3, "logs")).await.unwrap();
+        tx.send(make_envelope(Priority::P0, "cmd")).await.unwrap();
+        drop(tx);
+
+        transport.run_receive_loop().await;
+
+        assert_eq!(*received.lock().unwrap(), vec!["cmd", "logs"]);
+    }
+
+    #[tokio::test]
+    async fn test_receive_loop_reassembles_fragments_before_dispatch() {
+        use crate::packetization::Packetizer;
+
+        let (mut transport, tx) = QuicTransport::with_loopback();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_clone = received.clone();
+        transport
+            .on_receive(Box::new(move |envelope: Envelope| {
+                received_clone.lock().unwrap().push(envelope.payload);
+            }))
+            .await;
+
+        let original = Envelope {
+            payload: vec![7u8; 3000],
+            ..make_envelope(Priority::P2, "large")
+        };
+        let original_payload = original.payload.clone();
+        let fragments = Packetizer::new(1400).fragment(original).unwrap();
+        for fragment in fragments {
+            tx.send(fragment).await.unwrap();
+        }
+        drop(tx);
+
+        transport.run_receive_loop().await;
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0], original_payload);
+    }
+
+    #[tokio::test]
+    async fn test_receive_loop_returns_immediately_with_no_handler() {
+        let (mut transport, _tx) = QuicTransport::with_loopback();
+        transport.run_receive_loop().await;
+    }
 }